@@ -3,10 +3,43 @@ use std::{cell::RefCell, rc::Rc};
 use crate::types::prelude::*;
 
 pub fn interpret(src: &Syntax) -> SResult<Pointer> {
-    inner_interpret(src, rc_mut_new(State::new()))
+    inner_interpret(src, rc_mut_new(State::new())).map_err(stray_signal)
 }
 
-pub fn inner_interpret(src: &Syntax, state: RcMut<State>) -> SResult<Pointer> {
+/// Why evaluating something stopped before it had a value. `break` and `continue` travel up to their loop this way,
+/// along with the loop's label, which is empty when they're for the innermost loop
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Signal {
+    Error(String),
+    Break(String),
+    Continue(String),
+}
+
+impl From<String> for Signal {
+    fn from(err: String) -> Self {
+        Self::Error(err)
+    }
+}
+
+/// Like `SResult`, but it can also stop because of a `break` or `continue`
+pub type Flow<T> = Result<T, Signal>;
+
+/// Turn a `break` or `continue` that never found its loop into a normal error message. Errors are left alone
+pub fn stray_signal(signal: Signal) -> String {
+    let (keyword, label) = match signal {
+        Signal::Error(err) => return err,
+        Signal::Break(label) => ("break", label),
+        Signal::Continue(label) => ("continue", label),
+    };
+    if label.is_empty() {
+        format!("Can't `{keyword}` outside of a loop")
+    } else {
+        format!("There's no loop labeled `{label}` to `{keyword}`")
+    }
+}
+
+#[allow(clippy::too_many_lines)]
+pub fn inner_interpret(src: &Syntax, state: RcMut<State>) -> Flow<Pointer> {
     match src {
         Syntax::Statement(false, content, _) => {
             inner_interpret(content, state.clone())?;
@@ -28,22 +61,44 @@ pub fn inner_interpret(src: &Syntax, state: RcMut<State>) -> SResult<Pointer> {
             let evaluated = inner_interpret(content, state)?;
             Ok(-evaluated)
         }
+        Syntax::UnaryOperation(UnaryOperation::Not(count), content) => {
+            let evaluated = inner_interpret(content, state)?;
+            Ok(Pointer::from(evaluated.with_ref(|val| val.not(*count))))
+        }
         Syntax::Operation(lhs, op, rhs) => interpret_operation(lhs, *op, rhs, state),
         // Syntax::UnaryOperation(UnaryOperation::Call(args), operand) => {
         //     let func = inner_interpret(operand, state.clone())?;
         //     interpret_function(&func, args, state)
         // }
         Syntax::UnaryOperation(
-            unary @ (UnaryOperation::Increment | UnaryOperation::Decrement),
+            unary @ (UnaryOperation::Increment
+            | UnaryOperation::Decrement
+            | UnaryOperation::PreIncrement
+            | UnaryOperation::PreDecrement),
             operand,
         ) => {
-            let mut operand_ptr = inner_interpret(operand, state)?;
+            let mut operand_ptr = inner_interpret(operand, state.clone())?;
+            let previous = Pointer::from(operand_ptr.clone_inner());
+            if blocked_by_freeze(&operand_ptr, &state)? {
+                return Ok(previous);
+            }
             match unary {
-                UnaryOperation::Decrement => operand_ptr -= 1.0.into(),
-                UnaryOperation::Increment => operand_ptr += 1.0.into(),
+                UnaryOperation::Decrement | UnaryOperation::PreDecrement => {
+                    operand_ptr -= 1.0.into();
+                }
+                UnaryOperation::Increment | UnaryOperation::PreIncrement => {
+                    operand_ptr += 1.0.into();
+                }
                 _ => unreachable!(),
             }
-            Ok(operand_ptr)
+            if let Some(val) = operand_ptr.as_var() {
+                update_pointer(&val)?;
+            }
+            // postfix returns the value from before the update
+            Ok(match unary {
+                UnaryOperation::Increment | UnaryOperation::Decrement => previous,
+                _ => operand_ptr,
+            })
         }
         Syntax::Block(statements) => {
             let state = rc_mut_new(State::from_parent(state));
@@ -90,6 +145,11 @@ pub fn inner_interpret(src: &Syntax, state: RcMut<State>) -> SResult<Pointer> {
             Ok(Pointer::from(string_buf.as_ref()))
         }
         Syntax::UnaryOperation(UnaryOperation::Call(args), func) => {
+            if let Syntax::Operation(receiver, Operation::Dot, method) = &**func {
+                if let Syntax::Ident(method) = &**method {
+                    return call_method(receiver, method, args, state);
+                }
+            }
             let func = inner_interpret(func, state.clone())?;
             interpret_function(&func, args, state)
         }
@@ -97,19 +157,144 @@ pub fn inner_interpret(src: &Syntax, state: RcMut<State>) -> SResult<Pointer> {
         Syntax::Function(args, body) => {
             Ok(Pointer::from(Value::Function(args.clone(), *body.clone())))
         }
+        Syntax::Array(items) => {
+            let mut arr = Vec::with_capacity(items.len());
+            for item in items {
+                if let Syntax::Spread(inner) = item {
+                    let inner = inner_interpret(inner, state.clone())?;
+                    // spread items get their own slots, so editing them doesn't change the original
+                    for item in inner.with_ref(iterate)? {
+                        arr.push(Pointer::from(item.clone_inner()).convert(VarType::VarVar));
+                    }
+                } else {
+                    arr.push(inner_interpret(item, state.clone())?.convert(VarType::VarVar));
+                }
+            }
+            Ok(Pointer::from(Value::Array(arr)))
+        }
+        Syntax::Object(entries) => {
+            let mut obj = if state.borrow().config.borrow().preserve_object_order {
+                ObjectMap::ordered()
+            } else {
+                ObjectMap::new()
+            };
+            // entries are added in order, so later keys win
+            for (key, value) in entries {
+                let value = inner_interpret(value, state.clone())?;
+                let Some(key) = key else {
+                    let Value::Object(spread) = value.clone_inner() else {
+                        return Err(format!(
+                            "Can only spread objects into an object; got `{value}`"
+                        )
+                        .into());
+                    };
+                    for (key, value) in spread {
+                        obj.insert(
+                            key,
+                            Pointer::from(value.clone_inner()).convert(VarType::VarVar),
+                        );
+                    }
+                    continue;
+                };
+                obj.insert(Value::from(key.clone()), value.convert(VarType::VarVar));
+            }
+            Ok(Pointer::from(Value::Object(obj)))
+        }
+        Syntax::Spread(_) => {
+            Err(String::from("`...` only works inside of array and object literals").into())
+        }
+        Syntax::Literal(value) => Ok(Pointer::from((**value).clone())),
+        Syntax::Comprehension(body, ident, iterable, condition) => {
+            let iterable = inner_interpret(iterable, state.clone())?;
+            let mut arr = Vec::new();
+            for item in iterable.with_ref(iterate)? {
+                // the loop variable only exists inside each iteration
+                let inner_state = rc_mut_new(State::from_parent(state.clone()));
+                inner_state
+                    .borrow_mut()
+                    .insert(ident.clone(), item, Lifetime::Default);
+                if let Some(condition) = condition {
+                    let keep = inner_interpret(condition, inner_state.clone())?;
+                    if keep.with_ref(|keep| check_condition(keep, &inner_state))? != Boolean::True {
+                        continue;
+                    }
+                }
+                arr.push(inner_interpret(body, inner_state)?.convert(VarType::VarVar));
+            }
+            Ok(Pointer::from(Value::Array(arr)))
+        }
     }
 }
 
+/// Evaluate each argument of a builtin function
+fn eval_args(args: &[Syntax], state: &RcMut<State>) -> Flow<Vec<Value>> {
+    args.iter()
+        .map(|arg| Ok(inner_interpret(arg, state.clone())?.clone_inner()))
+        .collect()
+}
+
+/// Call a function with arguments that have already been evaluated. They're passed as literals, so it works with any
+/// kind of function
+fn call_function(func: &Pointer, args: Vec<Value>, state: &RcMut<State>) -> Flow<Pointer> {
+    let args = args
+        .into_iter()
+        .map(|arg| Syntax::Literal(Box::new(arg)))
+        .collect::<Vec<_>>();
+    interpret_function(func, &args, state.clone())
+}
+
+/// Call `receiver.method(args)`. Objects with a `method` key call it like normal; otherwise, the function named `method`
+/// is called with the receiver as its first argument, so `"abc".char_at(0)` is `char_at("abc", 0)`
+fn call_method(
+    receiver: &Syntax,
+    method: &Rc<str>,
+    args: &[Syntax],
+    state: RcMut<State>,
+) -> Flow<Pointer> {
+    let receiver = inner_interpret(receiver, state.clone())?;
+    let key = Value::from(method.clone());
+    let member = receiver.with_ref(|val| match val {
+        Value::Object(obj) => obj.get(&key).cloned(),
+        _ => None,
+    });
+    if let Some(member) = member {
+        return interpret_function(&member, args, state);
+    }
+    let func = state.borrow_mut().get(method.clone());
+    let mut method_state = State::from_parent(state);
+    // the receiver has to stay behind the same pointer, so it gets a name that can't be written in source code
+    let name: Rc<str> = "\0receiver".into();
+    method_state.insert(name.clone(), receiver, Lifetime::Default);
+    let args = std::iter::once(Syntax::Ident(name))
+        .chain(args.iter().cloned())
+        .collect::<Vec<_>>();
+    interpret_function(&func, &args, rc_mut_new(method_state))
+}
+
+/// Get the items of an array, or the characters of a string
+fn iterate(val: &Value) -> SResult<Vec<Pointer>> {
+    match val {
+        Value::Array(arr) => Ok(arr.clone()),
+        Value::String(str) => Ok(str
+            .chars()
+            .map(|ch| Pointer::from(Value::String(ch.to_string().into())))
+            .collect()),
+        other => Err(format!("Can't iterate over `{other}`")),
+    }
+}
+
+#[allow(clippy::too_many_lines)]
 fn interpret_operation(
     lhs: &Syntax,
     op: Operation,
     rhs: &Syntax,
     state: RcMut<State>,
-) -> SResult<Pointer> {
+) -> Flow<Pointer> {
     let mut lhs_eval = inner_interpret(lhs, state.clone())?;
     if let (Value::Object(_), Operation::Dot, Syntax::Ident(ident)) =
         (&*lhs_eval.make_const(), op, rhs)
     {
+        let frozen = lhs_eval.is_frozen();
         let inner_var = lhs_eval.make_var();
         let Value::Object(ref mut obj) = inner_var.borrow_mut().value else {
             panic!("Internal Compiler Error at {}:{}", file!(), line!())
@@ -120,11 +305,36 @@ fn interpret_operation(
             return Ok(val.clone());
         }
         let ptr = state.borrow().undefined.convert(VarType::VarVar);
+        if frozen {
+            // frozen objects don't get new keys, so give back a frozen stand-in
+            ptr.freeze();
+            return Ok(ptr);
+        }
         // println!("{ptr:?}");
         obj.insert(key, ptr.clone());
         return Ok(ptr);
     }
-    let rhs_eval = inner_interpret(rhs, state)?;
+    if op == Operation::Coalesce {
+        // only evaluate the right side if the left side is `undefined`
+        return if lhs_eval.with_ref(Value::is_default) {
+            inner_interpret(rhs, state)
+        } else {
+            Ok(lhs_eval)
+        };
+    }
+    let rhs_eval = inner_interpret(rhs, state.clone())?;
+    if matches!(
+        op,
+        Operation::AddEq
+            | Operation::SubEq
+            | Operation::Equal(1)
+            | Operation::MulEq
+            | Operation::DivEq
+            | Operation::ModEq
+    ) && blocked_by_freeze(&lhs_eval, &state)?
+    {
+        return Ok(lhs_eval);
+    }
     // println!("{lhs:?} op {rhs:?}");
     // println!("{lhs_eval:?} op {rhs_eval:?}");
     let ret = match op {
@@ -132,13 +342,29 @@ fn interpret_operation(
             lhs_eval.assign(&rhs_eval)?;
             rhs_eval
         }
-        Operation::Equal(precision) => lhs_eval.eq(&rhs_eval, precision - 1),
+        Operation::Equal(precision) => {
+            lhs_eval.eq_with(&rhs_eval, precision - 1, fuzzy_tolerance(&state))
+        }
         Operation::Add => lhs_eval + rhs_eval,
         Operation::Sub => lhs_eval - rhs_eval,
-        Operation::Mul => lhs_eval * rhs_eval,
-        Operation::Div => lhs_eval / rhs_eval,
-        Operation::Mod => lhs_eval % rhs_eval,
-        Operation::Dot => rhs_eval.with_ref(|rhs_eval| lhs_eval.dot(rhs_eval)),
+        Operation::Mul => {
+            check_repeat_len(&lhs_eval, &rhs_eval, &state)?;
+            lhs_eval * rhs_eval
+        }
+        Operation::Div => {
+            check_division(&lhs_eval, &rhs_eval, &state)?;
+            lhs_eval / rhs_eval
+        }
+        Operation::Mod => {
+            check_division(&lhs_eval, &rhs_eval, &state)?;
+            lhs_eval % rhs_eval
+        }
+        Operation::Dot => {
+            if lhs_eval.with_ref(|lhs| matches!(lhs, Value::Object(_))) {
+                rhs_eval.with_ref(|key| check_key(key, &state))?;
+            }
+            rhs_eval.with_ref(|rhs_eval| lhs_eval.dot(rhs_eval))?
+        }
         Operation::And => lhs_eval & rhs_eval,
         Operation::Or => lhs_eval | rhs_eval,
         Operation::AddEq => {
@@ -150,14 +376,17 @@ fn interpret_operation(
             lhs_eval
         }
         Operation::MulEq => {
+            check_repeat_len(&lhs_eval, &rhs_eval, &state)?;
             lhs_eval *= rhs_eval;
             lhs_eval
         }
         Operation::DivEq => {
+            check_division(&lhs_eval, &rhs_eval, &state)?;
             lhs_eval /= rhs_eval;
             lhs_eval
         }
         Operation::ModEq => {
+            check_division(&lhs_eval, &rhs_eval, &state)?;
             lhs_eval %= rhs_eval;
             lhs_eval
         }
@@ -165,7 +394,8 @@ fn interpret_operation(
         Operation::Le => Pointer::from(lhs_eval <= rhs_eval),
         Operation::Gt => Pointer::from(lhs_eval > rhs_eval),
         Operation::Ge => Pointer::from(lhs_eval >= rhs_eval),
-        Operation::Arrow => unreachable!(),
+        Operation::Pipe => call_function(&rhs_eval, vec![lhs_eval.clone_inner()], &state)?,
+        Operation::Arrow | Operation::Coalesce => unreachable!(),
     };
     if let (
         Some(val),
@@ -182,10 +412,96 @@ fn interpret_operation(
     Ok(ret)
 }
 
+/// Make sure multiplying a string won't make it longer than `max_string_length`, or multiply it by infinity
+fn check_repeat_len(lhs: &Pointer, rhs: &Pointer, state: &RcMut<State>) -> SResult<()> {
+    let max = state.borrow().config.borrow().max_string_length;
+    lhs.with_refs(rhs, |lhs, rhs| match (lhs, rhs) {
+        (Value::String(str), Value::Number(num)) => Value::check_repeat(str, *num, max),
+        _ => Ok(()),
+    })
+}
+
+/// With `strict_division`, make sure `rhs` isn't a zero divisor
+fn check_division(lhs: &Pointer, rhs: &Pointer, state: &RcMut<State>) -> SResult<()> {
+    if !state.borrow().config.borrow().strict_division {
+        return Ok(());
+    }
+    lhs.with_refs(rhs, Value::check_divisor)
+}
+
+/// How close numbers have to be to be equal with one `=`
+fn fuzzy_tolerance(state: &RcMut<State>) -> f64 {
+    let Tolerance(tolerance) = state.borrow().config.borrow().fuzzy_tolerance;
+    tolerance
+}
+
+/// Check if a write to `ptr` should be skipped because it's frozen. This is an error with `strict_freezing`
+fn blocked_by_freeze(ptr: &Pointer, state: &RcMut<State>) -> SResult<bool> {
+    if !ptr.is_frozen() {
+        return Ok(false);
+    }
+    if state.borrow().config.borrow().strict_freezing {
+        return Err(format!("Can't write to frozen value `{ptr}`"));
+    }
+    Ok(true)
+}
+
+/// With `strict_keys`, make sure `key` can be an object key
+fn check_key(key: &Value, state: &RcMut<State>) -> SResult<()> {
+    if key.is_scalar_key() || !state.borrow().config.borrow().strict_keys {
+        return Ok(());
+    }
+    Err(format!(
+        "Object keys have to be strings, numbers, booleans, or keywords; got `{key}`"
+    ))
+}
+
+/// With `strict_keys`, make sure every item of an array can be an object key, before any of them are made into one
+fn check_item_keys(arr: &Value, state: &RcMut<State>) -> SResult<()> {
+    for item in arr.as_array().unwrap_or_default() {
+        item.with_ref(|item| check_key(item, state))?;
+    }
+    Ok(())
+}
+
+/// With `strict_keys`, make sure the key of every `[key, value]` pair in an array can be an object key, before any of
+/// them are made into one
+fn check_pair_keys(pairs: &Value, state: &RcMut<State>) -> SResult<()> {
+    for pair in pairs.as_array().unwrap_or_default() {
+        pair.with_ref(|pair| {
+            pair.as_array()
+                .and_then(<[Pointer]>::first)
+                .map_or(Ok(()), |key| key.with_ref(|key| check_key(key, state)))
+        })?;
+    }
+    Ok(())
+}
+
+/// Check the precision `function` was given, which works like the number of `=`s in a comparison, minus one. It has to
+/// be a whole number from 0 to 3
+fn check_precision(precision: &Value, function: &str) -> SResult<u8> {
+    match precision {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        Value::Number(num) if num.fract() == 0.0 && (0.0..=3.0).contains(num) => Ok(*num as u8),
+        other => Err(format!(
+            "`{function}` requires a precision that's a whole number from 0 to 3; got `{other}`"
+        )),
+    }
+}
+
+/// Check if a condition is truthy, falsey, or maybeyey. With `strict_conditions`, maybeyey is an error
+fn check_condition(val: &Value, state: &RcMut<State>) -> SResult<Boolean> {
+    if state.borrow().config.borrow().strict_conditions {
+        val.coerce_bool_strict().map(Boolean::from)
+    } else {
+        Ok(val.bool())
+    }
+}
+
 fn update_pointer(val: &RefCell<MutValue>) -> SResult<()> {
     let listeners = val.borrow().event_listeners.clone();
     for (listener, state) in listeners {
-        inner_interpret(&listener, state)?;
+        inner_interpret(&listener, state).map_err(stray_signal)?;
     }
     let next_handles = val.borrow_mut().flush_next_handles();
     let new_value = val.borrow().value.clone();
@@ -204,17 +520,18 @@ fn update_pointer(val: &RefCell<MutValue>) -> SResult<()> {
 }
 
 #[allow(clippy::too_many_lines)]
-fn interpret_function(func: &Pointer, args: &[Syntax], state: RcMut<State>) -> SResult<Pointer> {
+fn interpret_function(func: &Pointer, args: &[Syntax], state: RcMut<State>) -> Flow<Pointer> {
     func.with_ref(|func_eval| match func_eval {
         Value::Keyword(Keyword::If) => {
             let [condition, body, ..] = args else {
                 return Err(String::from(
                     "If statement requires two arguments: condition and body",
-                ));
+                )
+                .into());
             };
             let condition_evaluated = inner_interpret(condition, state.clone())?;
             // println!("{condition_evaluated:?}");
-            let bool = condition_evaluated.with_ref(Value::bool);
+            let bool = condition_evaluated.with_ref(|val| check_condition(val, &state))?;
             if bool == Boolean::True {
                 inner_interpret(body, state)
             } else if let (Boolean::Maybe, Some(body)) = (bool, args.get(3)) {
@@ -226,15 +543,162 @@ fn interpret_function(func: &Pointer, args: &[Syntax], state: RcMut<State>) -> S
                 }
             }
         }
-        Value::Keyword(Keyword::Delete) => {
+        Value::Keyword(kw @ (Keyword::While | Keyword::DoWhile)) => {
+            let (condition, body, label) = match (kw, args) {
+                (Keyword::While, [condition, body, label @ ..])
+                | (Keyword::DoWhile, [body, condition, label @ ..])
+                    if label.len() <= 1 =>
+                {
+                    (condition, body, label.first())
+                }
+                (Keyword::While, _) => {
+                    return Err(String::from(
+                        "`while` requires a condition, a body, and an optional label",
+                    )
+                    .into())
+                }
+                _ => {
+                    return Err(String::from(
+                        "`do_while` requires a body, a condition, and an optional label",
+                    )
+                    .into())
+                }
+            };
+            let label = match label {
+                Some(label) => inner_interpret(label, state.clone())?.to_string(),
+                None => String::new(),
+            };
+            let mut result = state.borrow().undefined.clone();
+            // `do_while` runs its body once before checking anything
+            let mut check = *kw == Keyword::While;
+            loop {
+                // like `if`, only `true` counts, so `maybe` stops the loop
+                if check
+                    && inner_interpret(condition, state.clone())?
+                        .with_ref(|val| check_condition(val, &state))?
+                        != Boolean::True
+                {
+                    break;
+                }
+                check = true;
+                match inner_interpret(body, state.clone()) {
+                    Ok(value) => result = value,
+                    // unlabeled signals go to the innermost loop; labeled ones keep going until they find theirs
+                    Err(Signal::Break(to)) if to.is_empty() || to == label => break,
+                    Err(Signal::Continue(to)) if to.is_empty() || to == label => {}
+                    Err(signal) => return Err(signal),
+                }
+            }
+            Ok(result)
+        }
+        Value::Keyword(kw @ (Keyword::Break | Keyword::Continue)) => {
+            let label = match args {
+                [] => String::new(),
+                [label] => inner_interpret(label, state)?.to_string(),
+                _ => return Err(format!("`{kw}` takes an optional label").into()),
+            };
+            Err(if *kw == Keyword::Break {
+                Signal::Break(label)
+            } else {
+                Signal::Continue(label)
+            })
+        }
+        Value::Keyword(kw) => interpret_keyword(*kw, args, state),
+        Value::Memoized(memo) => {
+            let args = eval_args(args, &state)?;
+            if let Some(result) = memo.get(&args) {
+                return Ok(Pointer::from(result));
+            }
+            let func = Pointer::from(memo.func().clone());
+            let result = call_function(&func, args.clone(), &state)?.clone_inner();
+            memo.insert(args, result.clone());
+            Ok(Pointer::from(result))
+        }
+        Value::Builtin(name) => {
+            let builtin = state.borrow().builtins.borrow().get(name);
+            let Some(builtin) = builtin else {
+                return Err(format!("`{name}` isn't a registered builtin").into());
+            };
+            Ok(Pointer::from(builtin(&eval_args(args, &state)?)?))
+        }
+        Value::Object(obj) => {
+            let Some(call) = obj.get(&"call".into()) else {
+                return Err(format!("`Object({obj:?})` is not a function").into());
+            };
+            let mut new_state = State::from_parent(state);
+            new_state.insert("self".into(), func.clone(), Lifetime::Default);
+            interpret_function(call, args, rc_mut_new(new_state))
+        }
+        Value::Function(fn_args, body) => {
+            let mut inner_state = State::from_parent(state.clone());
+            for (idx, ident) in fn_args.iter().enumerate() {
+                let arg_eval = if let Some(syn) = args.get(idx) {
+                    inner_interpret(syn, state.clone())?
+                } else {
+                    state.borrow().undefined.clone()
+                };
+                inner_state.insert(ident.clone(), arg_eval, Lifetime::Default);
+            }
+            // loops outside of a function can't be controlled from inside of it
+            inner_interpret(body, rc_mut_new(inner_state))
+                .map_err(|signal| stray_signal(signal).into())
+        }
+        Value::String(str) => {
+            let [arg] = args else {
+                return Err("indexing string requires one argument".to_string().into());
+            };
+            let rhs = inner_interpret(arg, state.clone())?;
+            let Value::Number(rhs) = rhs.clone_inner() else {
+                return Err("indexing string requires number".to_string().into());
+            };
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let rhs = rhs as usize;
+            let char = str.chars().nth(rhs);
+            char.map_or_else(
+                || Ok(state.borrow().undefined.clone()),
+                |char| Ok(Pointer::from(Value::String(Rc::from(char.to_string())))),
+            )
+        }
+        other => multiply_call(other, args, state),
+    })
+}
+
+/// Call one of the keywords that work like functions. Any other keyword is multiplied by its argument, like other values
+/// that aren't functions
+#[allow(clippy::too_many_lines)]
+fn interpret_keyword(keyword: Keyword, args: &[Syntax], state: RcMut<State>) -> Flow<Pointer> {
+    match keyword {
+        Keyword::Delete => {
             if let [Syntax::Ident(key)] = args {
                 state.borrow_mut().delete(key.clone());
             }
             Ok(state.borrow().undefined.clone())
         }
-        Value::Keyword(Keyword::Forget) => {
+        Keyword::Equals => {
+            let (lhs, rhs, precision) = match args {
+                [lhs, rhs] => (lhs, rhs, state.borrow().config.borrow().default_precision),
+                [lhs, rhs, precision] => {
+                    let precision = inner_interpret(precision, state.clone())?;
+                    (
+                        lhs,
+                        rhs,
+                        precision.with_ref(|precision| check_precision(precision, "equals"))?,
+                    )
+                }
+                _ => {
+                    return Err(String::from(
+                        "`equals` requires two values and an optional precision",
+                    )
+                    .into())
+                }
+            };
+            let lhs = inner_interpret(lhs, state.clone())?;
+            let rhs = inner_interpret(rhs, state.clone())?;
+            Ok(lhs.eq_with(&rhs, precision, fuzzy_tolerance(&state)))
+        }
+        Keyword::Forget => {
             let [Syntax::Ident(ident)] = args else {
-                return Err(String::from("`forget` keyword requires one argument"));
+                return Err(String::from("`forget` keyword requires one argument").into());
             };
             let undefined = state.borrow().undefined.clone();
             state
@@ -242,9 +706,528 @@ fn interpret_function(func: &Pointer, args: &[Syntax], state: RcMut<State>) -> S
                 .insert(ident.clone(), undefined, Lifetime::Default);
             Ok(state.borrow().undefined.clone())
         }
-        Value::Keyword(Keyword::Previous) => {
+        Keyword::GroupBy => {
+            let [arr, key_fn] = args else {
+                return Err(String::from("`group_by` requires an array and a key function").into());
+            };
+            let arr = inner_interpret(arr, state.clone())?.clone_inner();
+            let key_fn = inner_interpret(key_fn, state.clone())?;
+            Ok(Pointer::from(arr.group_by(|item| {
+                let key = call_function(&key_fn, vec![item.clone()], &state)
+                    .map_err(stray_signal)?
+                    .clone_inner();
+                check_key(&key, &state)?;
+                Ok(key)
+            })?))
+        }
+        Keyword::SortByKey => {
+            let [arr, key_fn] = args else {
+                return Err(
+                    String::from("`sort_by_key` requires an array and a key function").into(),
+                );
+            };
+            let arr = inner_interpret(arr, state.clone())?.clone_inner();
+            let key_fn = inner_interpret(key_fn, state.clone())?;
+            Ok(Pointer::from(arr.sort_by_key(|item| {
+                Ok(call_function(&key_fn, vec![item.clone()], &state)
+                    .map_err(stray_signal)?
+                    .clone_inner())
+            })?))
+        }
+        Keyword::FlatMap => {
+            let [arr, func] = args else {
+                return Err(String::from("`flat_map` requires an array and a function").into());
+            };
+            let arr = inner_interpret(arr, state.clone())?.clone_inner();
+            let func = inner_interpret(func, state.clone())?;
+            Ok(Pointer::from(arr.flat_map(|item| {
+                Ok(call_function(&func, vec![item.clone()], &state)
+                    .map_err(stray_signal)?
+                    .clone_inner())
+            })?))
+        }
+        Keyword::Hashcode => {
             let [arg] = args else {
-                return Err(String::from("`previous` keyword requires one argument"));
+                return Err(String::from("`hashcode` keyword requires one argument").into());
+            };
+            let evaluated = inner_interpret(arg, state)?;
+            #[allow(clippy::cast_precision_loss)]
+            Ok(Pointer::from(evaluated.with_ref(Value::hashcode) as f64))
+        }
+        Keyword::Assert => {
+            let (condition, message) = match &eval_args(args, &state)?[..] {
+                [cond] => (
+                    check_condition(cond, &state)?,
+                    String::from("Assertion failed"),
+                ),
+                [cond, message] => (check_condition(cond, &state)?, message.to_string()),
+                _ => return Err(String::from("`assert` requires a condition and a message").into()),
+            };
+            // `maybe` isn't good enough for an assertion
+            if condition == Boolean::True {
+                Ok(state.borrow().undefined.clone())
+            } else {
+                Err(message.into())
+            }
+        }
+        Keyword::CharAt => {
+            let [string, index] = &eval_args(args, &state)?[..] else {
+                return Err(String::from("`char_at` requires a string and an index").into());
+            };
+            Ok(Pointer::from(string.char_at(index)?))
+        }
+        kw @ (Keyword::Ord | Keyword::Chr) => {
+            let [arg] = &eval_args(args, &state)?[..] else {
+                return Err(format!("`{kw}` requires one argument").into());
+            };
+            Ok(Pointer::from(if kw == Keyword::Ord {
+                arg.ord()
+            } else {
+                arg.chr()
+            }?))
+        }
+        kw @ (Keyword::ToCsv | Keyword::FromCsv) => {
+            let [arg] = &eval_args(args, &state)?[..] else {
+                return Err(format!("`{kw}` requires one argument").into());
+            };
+            Ok(Pointer::from(if kw == Keyword::ToCsv {
+                arg.to_csv()
+            } else {
+                arg.parse_csv()
+            }?))
+        }
+        kw @ (Keyword::ToQueryString | Keyword::FromQueryString) => {
+            let [arg] = &eval_args(args, &state)?[..] else {
+                return Err(format!("`{kw}` requires one argument").into());
+            };
+            Ok(Pointer::from(if kw == Keyword::ToQueryString {
+                arg.to_query_string()
+            } else {
+                arg.parse_query_string()
+            }?))
+        }
+        Keyword::Memoize => {
+            let [func] = &eval_args(args, &state)?[..] else {
+                return Err(String::from("`memoize` requires one function").into());
+            };
+            if !matches!(
+                func,
+                Value::Function(..) | Value::Keyword(_) | Value::Builtin(_) | Value::Memoized(_)
+            ) {
+                return Err(format!("`memoize` requires a function; got `{func}`").into());
+            }
+            Ok(Pointer::from(Value::Memoized(Rc::new(Memo::new(
+                func.clone(),
+            )))))
+        }
+        Keyword::Partial => {
+            let evaluated = eval_args(args, &state)?;
+            let Some((func, bound)) = evaluated.split_first() else {
+                return Err(String::from(
+                    "`partial` requires a function and the arguments to give it",
+                )
+                .into());
+            };
+            Ok(Pointer::from(func.partial(bound)?))
+        }
+        Keyword::Keys => {
+            let [obj] = &eval_args(args, &state)?[..] else {
+                return Err(String::from("`keys` requires one object").into());
+            };
+            Ok(Pointer::from(obj.keys()?))
+        }
+        Keyword::Lines => {
+            let [string] = &eval_args(args, &state)?[..] else {
+                return Err(String::from("`lines` requires one string").into());
+            };
+            Ok(Pointer::from(string.lines()))
+        }
+        kw @ (Keyword::SwapCase | Keyword::Capitalize | Keyword::TitleCase) => {
+            let [string] = &eval_args(args, &state)?[..] else {
+                return Err(format!("`{kw}` requires one string").into());
+            };
+            Ok(Pointer::from(match kw {
+                Keyword::SwapCase => string.swap_case(),
+                Keyword::Capitalize => string.capitalize(),
+                _ => string.title_case(),
+            }))
+        }
+        Keyword::ClampWidth => {
+            let (string, width, wide) = match &eval_args(args, &state)?[..] {
+                [string, width] => (string.clone(), width.clone(), false),
+                [string, width, wide] => (string.clone(), width.clone(), wide.bool() == Boolean::True),
+                _ => {
+                    return Err(String::from(
+                        "`clamp_width` requires a string, a width, and an optional boolean for wide characters",
+                    ).into())
+                }
+            };
+            Ok(Pointer::from(string.clamp_string_width(&width, wide)?))
+        }
+        kw @ (Keyword::Len | Keyword::ByteLen) => {
+            let [value] = &eval_args(args, &state)?[..] else {
+                return Err(format!("`{kw}` requires one argument").into());
+            };
+            Ok(Pointer::from(if kw == Keyword::Len {
+                value.len()
+            } else {
+                value.byte_len()
+            }?))
+        }
+        kw @ (Keyword::ToBase | Keyword::FromBase) => {
+            let [value, radix] = &eval_args(args, &state)?[..] else {
+                return Err(format!("`{kw}` requires a value and a base").into());
+            };
+            Ok(Pointer::from(if kw == Keyword::ToBase {
+                value.to_base(radix)
+            } else {
+                value.parse_base(radix)
+            }?))
+        }
+        kw @ (Keyword::IsFinite | Keyword::IsNan) => {
+            let [value] = &eval_args(args, &state)?[..] else {
+                return Err(format!("`{kw}` requires one argument").into());
+            };
+            Ok(Pointer::from(if kw == Keyword::IsFinite {
+                value.is_finite()
+            } else {
+                value.is_nan()
+            }))
+        }
+        Keyword::BoolToNumber => {
+            let [value] = &eval_args(args, &state)?[..] else {
+                return Err(String::from("`bool_to_number` requires one boolean").into());
+            };
+            Ok(Pointer::from(value.bool_to_number()?))
+        }
+        Keyword::Clamp => {
+            let [value, lo, hi] = &eval_args(args, &state)?[..] else {
+                return Err(String::from("`clamp` requires a value and two bounds").into());
+            };
+            Ok(Pointer::from(value.clamp(lo, hi)?))
+        }
+        Keyword::Collapse => {
+            let (value, probability) = match &eval_args(args, &state)?[..] {
+                [value] => (value.clone(), None),
+                [value, Value::Number(probability)] => (value.clone(), Some(*probability)),
+                _ => {
+                    return Err(String::from(
+                        "`collapse` requires a value and an optional probability",
+                    )
+                    .into())
+                }
+            };
+            let collapsed = value.collapse(probability, &mut state.borrow().rng.borrow_mut());
+            Ok(Pointer::from(collapsed))
+        }
+        Keyword::Count => {
+            let precision = state.borrow().config.borrow().default_precision;
+            match &eval_args(args, &state)?[..] {
+                [haystack] => {
+                    check_item_keys(haystack, &state)?;
+                    Ok(Pointer::from(haystack.count(None, precision)?))
+                }
+                [haystack, needle] => Ok(Pointer::from(haystack.count(Some(needle), precision)?)),
+                _ => Err(String::from("`count` requires one or two arguments").into()),
+            }
+        }
+        kw @ (Keyword::TruthyCount | Keyword::Vote) => {
+            let [container] = &eval_args(args, &state)?[..] else {
+                return Err(format!("`{kw}` requires an array or an object").into());
+            };
+            Ok(Pointer::from(if kw == Keyword::TruthyCount {
+                container.truthy_count()
+            } else {
+                container.vote()
+            }?))
+        }
+        Keyword::DeepGet => {
+            let [value, path] = &eval_args(args, &state)?[..] else {
+                return Err(String::from("`deep_get` requires a value and a path").into());
+            };
+            Ok(Pointer::from(value.deep_get(path)?))
+        }
+        Keyword::Diff => {
+            let default_precision = state.borrow().config.borrow().default_precision;
+            match &eval_args(args, &state)?[..] {
+                [old, new] => Ok(Pointer::from(old.diff(new, default_precision)?)),
+                [old, new, precision] => Ok(Pointer::from(
+                    old.diff(new, check_precision(precision, "diff")?)?,
+                )),
+                _ => Err(
+                    String::from("`diff` requires two objects and an optional precision").into(),
+                ),
+            }
+        }
+        Keyword::Format => {
+            let evaluated = eval_args(args, &state)?;
+            let Some((template, rest)) = evaluated.split_first() else {
+                return Err(String::from("`format` requires a template").into());
+            };
+            let (mut positional, mut named, mut strict) = (&[][..], &ObjectMap::new(), false);
+            for arg in rest {
+                match arg {
+                    Value::Array(arr) => positional = arr,
+                    Value::Object(obj) => named = obj,
+                    Value::Boolean(flag) => strict = *flag == Boolean::True,
+                    other => {
+                        return Err(format!(
+                            "`format` arguments must be an array, an object, or a strictness flag; got `{other}`"
+                        ).into())
+                    }
+                }
+            }
+            Ok(Pointer::from(template.format(positional, named, strict)?))
+        }
+        Keyword::Interpolate => {
+            let [template, env] = &eval_args(args, &state)?[..] else {
+                return Err(String::from(
+                    "`interpolate` requires a template and an object of variables",
+                )
+                .into());
+            };
+            Ok(Pointer::from(template.interpolate(env)?))
+        }
+        Keyword::Matches => {
+            let [text, pattern] = &eval_args(args, &state)?[..] else {
+                return Err(String::from("`matches` requires a string and a pattern").into());
+            };
+            Ok(Pointer::from(text.matches(pattern)))
+        }
+        Keyword::Replace => {
+            let [text, needle, replacement] = args else {
+                return Err(String::from(
+                    "`replace` requires a string, what to find, and what to replace it with",
+                )
+                .into());
+            };
+            let text = inner_interpret(text, state.clone())?.clone_inner();
+            let needle = inner_interpret(needle, state.clone())?.clone_inner();
+            let replacement = inner_interpret(replacement, state.clone())?;
+            // anything callable gets each match; anything else is put in as-is
+            let callable = replacement.with_ref(|val| {
+                matches!(
+                    val,
+                    Value::Function(..)
+                        | Value::Keyword(_)
+                        | Value::Builtin(_)
+                        | Value::Memoized(_)
+                )
+            });
+            Ok(Pointer::from(text.replace_all(&needle, |found| {
+                if callable {
+                    Ok(
+                        call_function(&replacement, vec![Value::from(found)], &state)
+                            .map_err(stray_signal)?
+                            .clone_inner(),
+                    )
+                } else {
+                    Ok(replacement.clone_inner())
+                }
+            })?))
+        }
+        Keyword::WithKey => {
+            let [obj, key, value] = &eval_args(args, &state)?[..] else {
+                return Err(
+                    String::from("`with_key` requires an object, a key, and a value").into(),
+                );
+            };
+            check_key(key, &state)?;
+            Ok(Pointer::from(obj.with_key(key, value)?))
+        }
+        Keyword::WithoutKey => {
+            let [obj, key] = &eval_args(args, &state)?[..] else {
+                return Err(String::from(
+                    "`without_key` requires an object and a key or array of keys",
+                )
+                .into());
+            };
+            Ok(Pointer::from(obj.without_key(key)?))
+        }
+        Keyword::Invert => {
+            let [obj] = &eval_args(args, &state)?[..] else {
+                return Err(String::from("`invert` requires one object").into());
+            };
+            Ok(Pointer::from(obj.invert()?))
+        }
+        Keyword::ApplyDefaults => {
+            let [obj, defaults] = &eval_args(args, &state)?[..] else {
+                return Err(
+                    String::from("`apply_defaults` requires an object and its defaults").into(),
+                );
+            };
+            Ok(Pointer::from(obj.apply_defaults(defaults)?))
+        }
+        kw @ (Keyword::Pick | Keyword::Omit) => {
+            let [obj, keys] = &eval_args(args, &state)?[..] else {
+                return Err(format!("`{kw}` requires an object and an array of keys").into());
+            };
+            Ok(Pointer::from(if kw == Keyword::Pick {
+                obj.pick(keys)
+            } else {
+                obj.omit(keys)
+            }?))
+        }
+        kw @ (Keyword::Arity | Keyword::Params) => {
+            let [func] = &eval_args(args, &state)?[..] else {
+                return Err(format!("`{kw}` requires one function").into());
+            };
+            Ok(Pointer::from(if kw == Keyword::Arity {
+                func.arity()
+            } else {
+                func.params()
+            }?))
+        }
+        Keyword::Zip => {
+            let [lhs, rhs] = &eval_args(args, &state)?[..] else {
+                return Err(String::from("`zip` requires two arrays").into());
+            };
+            Ok(Pointer::from(lhs.zip(rhs)?))
+        }
+        kw @ (Keyword::Take | Keyword::Drop | Keyword::Chunk | Keyword::Windows) => {
+            let [arr, count] = &eval_args(args, &state)?[..] else {
+                return Err(format!("`{kw}` requires an array and a number").into());
+            };
+            Ok(Pointer::from(match kw {
+                Keyword::Take => arr.take(count),
+                Keyword::Drop => arr.drop(count),
+                Keyword::Chunk => arr.chunk(count),
+                _ => arr.windows(count),
+            }?))
+        }
+        Keyword::Insert => {
+            let [arr, index, item] = &eval_args(args, &state)?[..] else {
+                return Err(
+                    String::from("`insert` requires an array, an index, and a value").into(),
+                );
+            };
+            Ok(Pointer::from(arr.insert(index, item.clone())?))
+        }
+        Keyword::RemoveAt => {
+            let [arr, index] = &eval_args(args, &state)?[..] else {
+                return Err(String::from("`remove_at` requires an array and an index").into());
+            };
+            Ok(Pointer::from(arr.remove_at(index)?))
+        }
+        Keyword::Enumerate => {
+            let [arr] = &eval_args(args, &state)?[..] else {
+                return Err(String::from("`enumerate` requires one array").into());
+            };
+            Ok(Pointer::from(arr.enumerate()?))
+        }
+        Keyword::Debug => {
+            let [arg] = args else {
+                return Err(String::from("`debug` requires one argument").into());
+            };
+            let evaluated = inner_interpret(arg, state)?;
+            Ok(Pointer::from(evaluated.debug_string().as_str()))
+        }
+        Keyword::Implies => {
+            let [lhs, rhs] = &eval_args(args, &state)?[..] else {
+                return Err(String::from("`implies` requires two values").into());
+            };
+            Ok(Pointer::from(lhs.implies(rhs)))
+        }
+        Keyword::SameIdentity => {
+            let [lhs, rhs] = args else {
+                return Err(String::from("`same_identity` requires two values").into());
+            };
+            let lhs = inner_interpret(lhs, state.clone())?;
+            let rhs = inner_interpret(rhs, state)?;
+            Ok(Pointer::from(lhs.same_identity(&rhs)))
+        }
+        kw @ (Keyword::Sum | Keyword::Product | Keyword::Average) => {
+            let [arr] = &eval_args(args, &state)?[..] else {
+                return Err(format!("`{kw}` requires one array").into());
+            };
+            Ok(Pointer::from(match kw {
+                Keyword::Sum => arr.sum(),
+                Keyword::Product => arr.product(),
+                _ => arr.average(),
+            }?))
+        }
+        Keyword::Coerce => {
+            let [value, type_name] = &eval_args(args, &state)?[..] else {
+                return Err(String::from("`coerce` requires a value and a type name").into());
+            };
+            if *type_name == Value::from("object") {
+                check_pair_keys(value, &state)?;
+            }
+            Ok(Pointer::from(value.coerce(type_name)?))
+        }
+        Keyword::ToObject => {
+            let [pairs] = &eval_args(args, &state)?[..] else {
+                return Err(String::from("`to_object` requires one array of pairs").into());
+            };
+            check_pair_keys(pairs, &state)?;
+            Ok(Pointer::from(pairs.to_object()?))
+        }
+        Keyword::Random => {
+            if !args.is_empty() {
+                return Err(String::from("`random` doesn't take any arguments").into());
+            }
+            Ok(Pointer::from(state.borrow().rng.borrow_mut().next_f64()))
+        }
+        Keyword::RandomInt => {
+            let [Value::Number(lo), Value::Number(hi)] = &eval_args(args, &state)?[..] else {
+                return Err(String::from("`random_int` requires two numbers").into());
+            };
+            let (lo, hi) = (lo.ceil(), hi.floor());
+            if lo > hi {
+                return Err(format!("There aren't any whole numbers from {lo} to {hi}").into());
+            }
+            let roll = state.borrow().rng.borrow_mut().next_f64();
+            Ok(Pointer::from(roll.mul_add(hi - lo + 1.0, lo).floor()))
+        }
+        Keyword::Sample => {
+            let (arr, count) = match &eval_args(args, &state)?[..] {
+                [arr] => (arr.clone(), None),
+                [arr, count] => (arr.clone(), Some(count.clone())),
+                _ => {
+                    return Err(
+                        String::from("`sample` requires an array and an optional count").into(),
+                    )
+                }
+            };
+            let sampled = arr.sample(count.as_ref(), &mut state.borrow().rng.borrow_mut())?;
+            Ok(Pointer::from(sampled))
+        }
+        Keyword::Shuffle => {
+            let [arr] = &eval_args(args, &state)?[..] else {
+                return Err(String::from("`shuffle` requires one array").into());
+            };
+            let shuffled = arr.shuffle(&mut state.borrow().rng.borrow_mut())?;
+            Ok(Pointer::from(shuffled))
+        }
+        Keyword::Seed => {
+            let [Value::Number(seed)] = &eval_args(args, &state)?[..] else {
+                return Err(String::from("`seed` requires a number").into());
+            };
+            *state.borrow().rng.borrow_mut() = Rng::seeded(seed.to_bits());
+            Ok(state.borrow().undefined.clone())
+        }
+        function @ (Keyword::Cos
+        | Keyword::Exp
+        | Keyword::Ln
+        | Keyword::Log
+        | Keyword::Sin
+        | Keyword::Sqrt
+        | Keyword::Tan) => {
+            let [value] = &eval_args(args, &state)?[..] else {
+                return Err(format!("`{function}` requires one argument").into());
+            };
+            Ok(Pointer::from(value.math(function)))
+        }
+        Keyword::Sign => {
+            let [value] = &eval_args(args, &state)?[..] else {
+                return Err(String::from("`sign` requires one argument").into());
+            };
+            Ok(Pointer::from(value.sign()))
+        }
+        Keyword::Range => Ok(Pointer::from(Value::range(&eval_args(args, &state)?)?)),
+        Keyword::Previous => {
+            let [arg] = args else {
+                return Err(String::from("`previous` keyword requires one argument").into());
             };
             let evaluated = inner_interpret(arg, state.clone())?;
             match evaluated.as_var() {
@@ -255,20 +1238,20 @@ fn interpret_function(func: &Pointer, args: &[Syntax], state: RcMut<State>) -> S
                 None => Ok(state.borrow().undefined.clone()),
             }
         }
-        Value::Keyword(Keyword::Current) => {
+        Keyword::Current => {
             let [arg] = args else {
-                return Err(String::from("`current` keyword requires one argument"));
+                return Err(String::from("`current` keyword requires one argument").into());
             };
             inner_interpret(arg, state)
         }
-        Value::Keyword(Keyword::Next) => {
+        Keyword::Next => {
             let [arg] = args else {
-                return Err(String::from("`next` keyword requires one argument"));
+                return Err(String::from("`next` keyword requires one argument").into());
             };
             let evaluated = inner_interpret(arg, state)?;
             let next_ptr = Pointer::ConstVar(rc_mut_new(Value::empty_object().into()));
             evaluated.as_var().map_or_else(
-                || Err(String::from("`next` keyword requires a mutable value")),
+                || Err(String::from("`next` keyword requires a mutable value").into()),
                 |eval| {
                     eval.borrow_mut().add_next_handle(next_ptr.clone());
                     // println!("{eval:?}");
@@ -277,11 +1260,12 @@ fn interpret_function(func: &Pointer, args: &[Syntax], state: RcMut<State>) -> S
                 },
             )
         }
-        Value::Keyword(Keyword::When) => {
+        Keyword::When => {
             let [condition, body] = args else {
                 return Err(String::from(
                     "`when` keyword requires two arguments; condition and body",
-                ));
+                )
+                .into());
             };
             let idents = find_idents_in_syntax(condition);
             for ident in idents {
@@ -298,11 +1282,20 @@ fn interpret_function(func: &Pointer, args: &[Syntax], state: RcMut<State>) -> S
             }
             Ok(state.borrow().undefined.clone())
         }
-        Value::Keyword(Keyword::Function) => {
+        Keyword::Freeze => {
+            let [arg] = args else {
+                return Err(String::from("`freeze` requires one argument").into());
+            };
+            let ptr = inner_interpret(arg, state)?;
+            ptr.freeze();
+            Ok(ptr)
+        }
+        Keyword::Function => {
             let [Syntax::Ident(name), args, body] = args else {
                 return Err(format!(
                     "Invalid arguments for `function`: `{args:?}`; expected name, args, and body"
-                ));
+                )
+                .into());
             };
             let args = match args {
                 Syntax::Block(args) => args.clone(),
@@ -314,18 +1307,19 @@ fn interpret_function(func: &Pointer, args: &[Syntax], state: RcMut<State>) -> S
                     Syntax::Ident(str) => Ok(str),
                     other => Err(format!("Invalid parameter name: `{other}`")),
                 })
-                .collect::<Result<_, _>>()?;
+                .collect::<SResult<_>>()?;
             let inner_val = Value::Function(args, body.clone());
             state
                 .borrow_mut()
                 .insert(name.clone(), Pointer::from(inner_val), Lifetime::Default);
             Ok(state.borrow().undefined.clone())
         }
-        Value::Keyword(Keyword::Class) => {
+        Keyword::Class => {
             let [Syntax::Ident(name), Syntax::Block(body)] = args else {
                 return Err(format!(
                     "Invalid arguments for `class`: `{args:?}`; expected name and body"
-                ));
+                )
+                .into());
             };
             let inner_value = Value::Class(body.clone());
             state.borrow_mut().insert(
@@ -335,42 +1329,45 @@ fn interpret_function(func: &Pointer, args: &[Syntax], state: RcMut<State>) -> S
             );
             Ok(state.borrow().undefined.clone())
         }
-        Value::Keyword(Keyword::New) => {
+        Keyword::New => {
             let [class] = args else {
-                return Err(format!(
-                    "Invalid arguments for `new`: `{args:?}`; expected a class"
-                ));
+                return Err(
+                    format!("Invalid arguments for `new`: `{args:?}`; expected a class").into(),
+                );
             };
             let class_pointer = inner_interpret(class, state.clone())?;
             let Some(class_ref) = class_pointer.as_var() else {
                 return Err(format!(
                     "Expected a mutable reference to a class; got `{class_pointer:?}`"
-                ));
+                )
+                .into());
             };
             class_ref.borrow_mut().assign(Value::empty_object());
             let Some(Value::Class(class_body)) = class_ref.borrow().previous.clone() else {
                 return Err(format!(
                     "Expected a mutable reference to a class; got `{:?}`",
                     class_ref.borrow()
-                ));
+                )
+                .into());
             };
             let inner_state = rc_mut_new(State::from_parent(state));
             for statement in class_body {
                 inner_interpret(&statement, inner_state.clone())?;
             }
+            #[allow(clippy::mutable_key_type)]
             let inner_obj = inner_state.borrow().locals_to_object();
-            Ok(Pointer::from(Value::Object(inner_obj)))
+            Ok(Pointer::from(Value::Object(inner_obj.into())))
         }
-        Value::Keyword(Keyword::Eval) => {
+        Keyword::Eval => {
             let [body] = args else {
-                return Err(format!(
-                    "You can only `eval` one thing at a time; got `{args:?}`"
-                ));
+                return Err(
+                    format!("You can only `eval` one thing at a time; got `{args:?}`").into(),
+                );
             };
             let text = inner_interpret(body, state.clone())?.to_string();
             // #[cfg(debug_assertions)]
             // println!("Evaluating Inner: {text}");
-            let tokens = crate::lexer::tokenize(&text)?;
+            let tokens = crate::lexer::tokenize_with(&text, &state.borrow().config.borrow())?;
             // #[cfg(debug_assertions)]
             // println!("Evaluating Tokens: {tokens:?}");
             let syntax = crate::parser::parse(tokens)?;
@@ -378,61 +1375,36 @@ fn interpret_function(func: &Pointer, args: &[Syntax], state: RcMut<State>) -> S
             // println!("Evaluating Syntax: {syntax:?}");
             inner_interpret(&syntax, state)
         }
-        Value::Object(obj) => {
-            let Some(call) = obj.get(&"call".into()) else {
-                return Err(format!("`Object({obj:?})` is not a function"));
-            };
-            let mut new_state = State::from_parent(state);
-            new_state.insert("self".into(), func.clone(), Lifetime::Default);
-            interpret_function(call, args, rc_mut_new(new_state))
-        }
-        Value::Function(fn_args, body) => {
-            let mut inner_state = State::from_parent(state.clone());
-            for (idx, ident) in fn_args.iter().enumerate() {
-                let arg_eval = if let Some(syn) = args.get(idx) {
-                    inner_interpret(syn, state.clone())?
-                } else {
-                    state.borrow().undefined.clone()
-                };
-                inner_state.insert(ident.clone(), arg_eval, Lifetime::Default);
-            }
-            inner_interpret(body, rc_mut_new(inner_state))
-        }
-        Value::String(str) => {
-            let [arg] = args else {
-                return Err("indexing string requires one argument".to_string());
-            };
-            let rhs = inner_interpret(arg, state.clone())?;
-            let Value::Number(rhs) = rhs.clone_inner() else {
-                return Err("indexing string requires number".to_string());
-            };
-            let rhs = rhs as usize;
-            let char = str.chars().nth(rhs);
-            char.map_or_else(
-                || Ok(state.borrow().undefined.clone()),
-                |char| Ok(Pointer::from(Value::String(Rc::from(char.to_string())))),
-            )
-        }
-        other => {
-            let [arg] = args else {
-                return Err(format!("`{other}` is not a function"));
-            };
-            let rhs = inner_interpret(arg, state)?;
-            Ok((other.clone() * rhs.clone_inner()).into())
-        }
-    })
+        other => multiply_call(&Value::Keyword(other), args, state),
+    }
+}
+
+/// Calling a value that isn't a function with one argument multiplies them, so `2(3)` is `6`
+fn multiply_call(value: &Value, args: &[Syntax], state: RcMut<State>) -> Flow<Pointer> {
+    let [arg] = args else {
+        return Err(format!("`{value}` is not a function").into());
+    };
+    let rhs = inner_interpret(arg, state)?;
+    Ok((value.clone() * rhs.clone_inner()).into())
 }
 
 fn find_idents_in_syntax(syn: &Syntax) -> Vec<Rc<str>> {
     match syn {
         Syntax::Ident(id) => vec![id.clone()],
-        Syntax::Block(stmts) => stmts.iter().flat_map(find_idents_in_syntax).collect(),
+        Syntax::Block(stmts) | Syntax::Array(stmts) => {
+            stmts.iter().flat_map(find_idents_in_syntax).collect()
+        }
         Syntax::UnaryOperation(UnaryOperation::Call(args), func) => args
             .iter()
             .chain(std::iter::once(&**func))
             .flat_map(find_idents_in_syntax)
             .collect(),
-        Syntax::UnaryOperation(UnaryOperation::Negate, syn) => find_idents_in_syntax(syn),
+        Syntax::UnaryOperation(UnaryOperation::Negate | UnaryOperation::Not(_), syn)
+        | Syntax::Spread(syn) => find_idents_in_syntax(syn),
+        Syntax::Object(entries) => entries
+            .iter()
+            .flat_map(|(_, value)| find_idents_in_syntax(value))
+            .collect(),
         Syntax::Operation(lhs, _, rhs) => find_idents_in_syntax(lhs)
             .into_iter()
             .chain(find_idents_in_syntax(rhs))
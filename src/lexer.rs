@@ -1,18 +1,190 @@
-use std::iter::Peekable;
+use std::{cell::Cell, iter::Peekable};
 
 use crate::types::prelude::*;
 
 pub fn tokenize(source: &str) -> SResult<Vec<Token>> {
-    let mut chars = source.chars().peekable();
+    tokenize_with(source, &Config::default())
+}
+
+pub fn tokenize_with(source: &str, config: &Config) -> SResult<Vec<Token>> {
+    Ok(tokenize_with_starts(source, config)?
+        .into_iter()
+        .map(|(tok, _)| tok)
+        .collect())
+}
+
+/// Tokenize, keeping track of the index of the character each token starts at. Pass these to `position` to find
+/// where a token is
+pub fn tokenize_with_starts(source: &str, config: &Config) -> SResult<Vec<(Token, usize)>> {
+    let consumed = Cell::new(0usize);
+    let mut chars = source
+        .chars()
+        .inspect(|_| consumed.set(consumed.get() + 1))
+        .peekable();
     let mut token_stream = Vec::new();
     while chars.peek().is_some() {
-        if let Some(tok) = inner_tokenize(&mut chars)? {
-            token_stream.push(tok);
+        // peeking already counted the first character of the token
+        let start = consumed.get() - 1;
+        match inner_tokenize(&mut chars, config) {
+            Ok(Some(tok)) => {
+                token_stream.push((tok, start));
+                if let Err(err) = check_token_count(token_stream.len(), config) {
+                    let (line, column) = position(source, start);
+                    return Err(format!("{err} at {line}:{column}"));
+                }
+            }
+            Ok(None) => {}
+            Err(err) => {
+                let (line, column) = position(source, consumed.get().saturating_sub(1));
+                return Err(format!("{err} at {line}:{column}"));
+            }
         }
     }
+    if config.newline_statements {
+        token_stream = end_lines(source, &token_stream);
+        check_token_count(token_stream.len(), config)?;
+    }
     Ok(token_stream)
 }
 
+/// Make sure there aren't more tokens than `max_tokens`, so huge programs stop lexing before they use up all the memory
+fn check_token_count(count: usize, config: &Config) -> SResult<()> {
+    match config.max_tokens {
+        Some(max) if count > max => Err(format!("Program has more than {max} tokens")),
+        _ => Ok(()),
+    }
+}
+
+/// Put a `!` before every newline that could end a statement. That's a newline right after something that finishes
+/// an expression, that isn't inside of `()` or `[]`, and that isn't followed by something that keeps the expression
+/// going, like `.` or a closing bracket
+fn end_lines(source: &str, tokens: &[(Token, usize)]) -> Vec<(Token, usize)> {
+    let chars = source.chars().collect::<Vec<_>>();
+    let mut brackets = Vec::new();
+    let mut prev: Option<Token> = None;
+    let mut ended = Vec::with_capacity(tokens.len());
+    for (idx, (tok, start)) in tokens.iter().enumerate() {
+        match tok {
+            Token::LParen | Token::LSquare | Token::LSquirrely => brackets.push(tok.clone()),
+            Token::RParen | Token::RSquare | Token::RSquirrely => {
+                brackets.pop();
+            }
+            Token::Space(_) => {
+                let next = tokens.get(idx + 1);
+                let end = next.map_or(chars.len(), |(_, start)| *start);
+                let ends_statement = chars[*start..end].contains(&'\n')
+                    && matches!(brackets.last(), None | Some(Token::LSquirrely))
+                    && matches!(
+                        prev,
+                        Some(
+                            Token::Ident(_)
+                                | Token::Keyword(_)
+                                | Token::String(_)
+                                | Token::RParen
+                                | Token::RSquare
+                                | Token::RSquirrely
+                                | Token::PlusPlus
+                                | Token::TackTack
+                        )
+                    )
+                    && !matches!(
+                        next,
+                        Some((
+                            Token::RParen
+                                | Token::RSquare
+                                | Token::RSquirrely
+                                | Token::Dot
+                                | Token::Bang(_)
+                                | Token::Question(_),
+                            _
+                        ))
+                    );
+                if ends_statement {
+                    ended.push((Token::Bang(1), *start));
+                }
+                ended.push((tok.clone(), *start));
+                continue;
+            }
+            Token::Comment(_) => {
+                ended.push((tok.clone(), *start));
+                continue;
+            }
+            _ => {}
+        }
+        prev = Some(tok.clone());
+        ended.push((tok.clone(), *start));
+    }
+    ended
+}
+
+/// How much a whitespace character counts for when grouping operations. `'\n'` counts as multiple whitespaces
+pub const fn whitespace_weight(ch: char) -> u32 {
+    match ch {
+        '\n' => 3,
+        _ => 1,
+    }
+}
+
+/// get the 1-indexed line and column of the character at `idx`
+pub fn position(source: &str, idx: usize) -> (usize, usize) {
+    let mut line = 1;
+    let mut column = 1;
+    for ch in source.chars().take(idx) {
+        if ch == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+    (line, column)
+}
+
+/// Identifiers start with a character Unicode says can start one (`XID_Start`, which is the letters of every
+/// alphabet), `_`, or an emoji
+fn is_ident_start(ch: char) -> bool {
+    unicode_ident::is_xid_start(ch) || ch == '_' || is_emoji(ch)
+}
+
+/// After the first character, identifiers can have anything Unicode says can continue one (`XID_Continue`, which adds
+/// digits and combining marks), more emoji, and the joiner and variation selector that glue emoji together
+fn is_ident_continue(ch: char) -> bool {
+    unicode_ident::is_xid_continue(ch) || is_emoji(ch) || matches!(ch, '\u{200D}' | '\u{FE0F}')
+}
+
+/// Emoji and other pictures
+const fn is_emoji(ch: char) -> bool {
+    matches!(ch, '\u{1F000}'..='\u{1FAFF}' | '\u{2600}'..='\u{27BF}' | '\u{2B00}'..='\u{2BFF}')
+}
+
+/// Outside of strict mode, identifiers can also have the ASCII symbols that don't start another token in them
+fn is_lenient_ident_continue(ch: char) -> bool {
+    is_ident_continue(ch) || "#$@^~\\".contains(ch)
+}
+
+/// Lex a word in strict mode. Words that start with a digit are numbers and can only contain digits
+fn strict_word<T: Iterator<Item = char>>(first: char, chars: &mut Peekable<T>) -> SResult<Token> {
+    let is_number = first.is_numeric();
+    if !is_number && !is_ident_start(first) {
+        return Err(format!("Unexpected character `{first}`"));
+    }
+    let mut buf = String::from(first);
+    while let Some(&next) = chars.peek() {
+        if (is_number && next.is_numeric()) || (!is_number && is_ident_continue(next)) {
+            buf.push(next);
+            chars.next();
+        } else if is_number && is_ident_start(next) {
+            return Err(format!(
+                "Unexpected character `{next}`; identifiers can't start with a digit"
+            ));
+        } else {
+            // leave invalid characters for the next token so the error points at them
+            break;
+        }
+    }
+    Ok(Keyword::reserved(&buf).map_or_else(|| Token::Ident(buf.into()), Token::Keyword))
+}
+
 macro_rules! multi_character_pattern {
     ($chars:ident $just:expr; {$($char:expr => $eq:expr),*}) => {
         match $chars.peek() {
@@ -25,13 +197,51 @@ macro_rules! multi_character_pattern {
     };
 }
 
-fn lex_string<T: Iterator<Item = char>>(chars: &mut Peekable<T>, end: char) -> SResult<Token> {
+/// Get the next character inside of a string's `{}`, which has to be closed before the end of the file
+fn next_in_braces<T: Iterator<Item = char>>(chars: &mut Peekable<T>) -> SResult<char> {
+    chars
+        .next()
+        .ok_or_else(|| String::from("Unterminated string interpolation"))
+}
+
+/// Split a template into the segments it would have if it were inside of quotes
+pub fn lex_template(template: &str) -> SResult<Vec<StringSegment>> {
+    let Token::String(segments) = lex_string(&mut template.chars().peekable(), None, None)? else {
+        unreachable!()
+    };
+    Ok(segments)
+}
+
+/// Lex the rest of a string literal, up to and including the `end` quote. Without an `end`, the string is the rest of
+/// the input. With a `max`, it stops as soon as the literal has more characters than that
+fn lex_string<T: Iterator<Item = char>>(
+    chars: &mut Peekable<T>,
+    end: Option<char>,
+    max: Option<usize>,
+) -> SResult<Token> {
     let mut outer_buf = Vec::new();
     let mut string_buf = String::new();
-    while let Some(next) = chars.next() {
-        if next == end {
+    let mut length = 0;
+    let mut count = || {
+        length += 1;
+        match max {
+            Some(max) if length > max => Err(format!(
+                "String literal is over the limit of {max} characters"
+            )),
+            _ => Ok(()),
+        }
+    };
+    loop {
+        let Some(next) = chars.next() else {
+            if end.is_none() {
+                break;
+            }
+            return Err(String::from("Unterminated string literal"));
+        };
+        if Some(next) == end {
             break;
         }
+        count()?;
         if matches!(next, '$' | '£' | '¥') && chars.peek() == Some(&'{') {
             chars.next();
             if !string_buf.is_empty() {
@@ -39,7 +249,9 @@ fn lex_string<T: Iterator<Item = char>>(chars: &mut Peekable<T>, end: char) -> S
                     core::mem::take(&mut string_buf).into(),
                 ));
             }
-            for next in chars.by_ref() {
+            loop {
+                let next = next_in_braces(chars)?;
+                count()?;
                 if next == '}' {
                     if !string_buf.is_empty() {
                         outer_buf.push(StringSegment::Ident(
@@ -52,19 +264,22 @@ fn lex_string<T: Iterator<Item = char>>(chars: &mut Peekable<T>, end: char) -> S
             }
         } else if next == '{' {
             let mut ident_buf = String::new();
-            for next in chars.by_ref() {
+            loop {
+                let next = next_in_braces(chars)?;
+                count()?;
                 if next == '}' {
                     break;
                 }
                 ident_buf.push(next);
             }
-            if matches!(chars.peek(), Some('€' | '円' | '₽')) {
+            let currency = matches!(chars.peek(), Some('€' | '円' | '₽'));
+            if (currency || ident_buf.contains('$')) && !string_buf.is_empty() {
+                outer_buf.push(StringSegment::String(
+                    core::mem::take(&mut string_buf).into(),
+                ));
+            }
+            if currency {
                 chars.next();
-                if !string_buf.is_empty() {
-                    outer_buf.push(StringSegment::String(
-                        core::mem::take(&mut string_buf).into(),
-                    ));
-                }
                 outer_buf.push(StringSegment::Ident(ident_buf.into()));
             } else if ident_buf.contains('$') {
                 let bits = ident_buf.split('$').collect::<Vec<_>>();
@@ -75,6 +290,7 @@ fn lex_string<T: Iterator<Item = char>>(chars: &mut Peekable<T>, end: char) -> S
                 string_buf.push('}');
             }
         } else if next == '\\' {
+            count()?;
             string_buf.push(next);
             string_buf.push(
                 chars
@@ -91,6 +307,28 @@ fn lex_string<T: Iterator<Item = char>>(chars: &mut Peekable<T>, end: char) -> S
     Ok(Token::String(outer_buf))
 }
 
+/// Read the rest of a `//` or `/* */` comment, after its first `/`. Line comments stop before the newline
+fn lex_comment<T: Iterator<Item = char>>(chars: &mut Peekable<T>) -> SResult<String> {
+    let mut comment = String::from('/');
+    if chars.next_if_eq(&'/').is_some() {
+        comment.push('/');
+        while let Some(next) = chars.next_if(|ch| *ch != '\n') {
+            comment.push(next);
+        }
+        return Ok(comment);
+    }
+    chars.next();
+    comment.push('*');
+    while let Some(next) = chars.next() {
+        comment.push(next);
+        if next == '*' && chars.next_if_eq(&'/').is_some() {
+            comment.push('/');
+            return Ok(comment);
+        }
+    }
+    Err(String::from("Unterminated block comment"))
+}
+
 fn count_char<T: Iterator<Item = char>, F: Fn(u8) -> Token>(
     chars: &mut Peekable<T>,
     tok: char,
@@ -104,7 +342,10 @@ fn count_char<T: Iterator<Item = char>, F: Fn(u8) -> Token>(
     typ(count)
 }
 
-fn inner_tokenize<T: Iterator<Item = char>>(chars: &mut Peekable<T>) -> SResult<Option<Token>> {
+fn inner_tokenize<T: Iterator<Item = char>>(
+    chars: &mut Peekable<T>,
+    config: &Config,
+) -> SResult<Option<Token>> {
     let Some(char) = chars.next() else {
         return Err(String::from("Unexpected end of file"));
     };
@@ -117,10 +358,16 @@ fn inner_tokenize<T: Iterator<Item = char>>(chars: &mut Peekable<T>) -> SResult<
         ']' => Token::RSquare,
         ';' => Token::Semicolon,
         ':' => Token::Colon,
+        '.' if chars.next_if_eq(&'.').is_some() => {
+            if chars.next_if_eq(&'.').is_none() {
+                return Err(String::from("Unexpected `..`; did you mean `...`?"));
+            }
+            Token::Ellipsis
+        }
         '.' => Token::Dot,
         ',' => Token::Comma,
         '&' => Token::And,
-        '|' => Token::Or,
+        '|' => multi_character_pattern!(chars Token::Or; {'>' => Token::Pipe}),
         '+' => {
             multi_character_pattern!(chars Token::Plus; {'=' => Token::PlusEq, '+' => Token::PlusPlus})
         }
@@ -128,47 +375,50 @@ fn inner_tokenize<T: Iterator<Item = char>>(chars: &mut Peekable<T>) -> SResult<
             multi_character_pattern!(chars Token::Tack; {'=' => Token::TackEq, '>' => Token::Arrow, '-' => Token::TackTack})
         }
         '*' => multi_character_pattern!(chars Token::Star; {'=' => Token::StarEq}),
+        '/' if matches!(chars.peek(), Some('/' | '*')) => {
+            let comment = lex_comment(chars)?;
+            if !config.preserve_comments {
+                return Ok(None);
+            }
+            Token::Comment(comment.into())
+        }
         '/' => multi_character_pattern!(chars Token::Slash; {'=' => Token::SlashEq}),
         '%' => multi_character_pattern!(chars Token::Percent; {'=' => Token::PercentEq}),
         '<' => multi_character_pattern!(chars Token::LCaret; {'=' => Token::LCaretEq}),
         '>' => multi_character_pattern!(chars Token::RCaret; {'=' => Token::RCaretEq}),
-        '"' => lex_string(chars, '"')?,
-        '\'' => lex_string(chars, '\'')?,
-        '`' => lex_string(chars, '`')?,
-        '«' => lex_string(chars, '»')?,
-        '»' => lex_string(chars, '«')?,
-        '„' => lex_string(chars, '“')?,
+        '"' | '\'' | '`' | '«' | '»' | '„' => {
+            let end = match char {
+                '«' => '»',
+                '»' => '«',
+                '„' => '“',
+                other => other,
+            };
+            lex_string(chars, Some(end), config.max_string_length)?
+        }
         '=' => count_char(chars, '=', Token::Equal),
         '!' => count_char(chars, '!', Token::Bang),
         '?' => count_char(chars, '?', Token::Question),
         _ => {
             if char.is_whitespace() {
-                let mut whitespace_count = 1;
+                let mut whitespace_count: u32 = 1;
                 while let Some(tok) = chars.peek() {
                     if tok.is_whitespace() {
-                        // `'\n'` counts as multiple whitespaces
-                        whitespace_count += match tok {
-                            '\n' => 3,
-                            _ => 1,
-                        };
+                        whitespace_count = whitespace_count.saturating_add(whitespace_weight(*tok));
                         chars.next();
                     } else {
                         break;
                     }
                 }
                 Token::Space(whitespace_count)
+            } else if config.strict_lexing {
+                strict_word(char, chars)?
             } else {
                 let mut ident_buf = String::from(char);
-                while let Some(next) = chars.peek() {
-                    match inner_tokenize(&mut std::iter::once(*next).peekable()) {
-                        Ok(Some(Token::Ident(id))) => {
-                            ident_buf.push_str(&id);
-                            chars.next();
-                        }
-                        _ => break,
-                    }
+                while let Some(next) = chars.next_if(|ch| is_lenient_ident_continue(*ch)) {
+                    ident_buf.push(next);
                 }
-                Token::Ident(ident_buf.into())
+                Keyword::reserved(&ident_buf)
+                    .map_or_else(|| Token::Ident(ident_buf.into()), Token::Keyword)
             }
         }
     }))
@@ -11,7 +11,7 @@ use rustyline::{error::ReadlineError, DefaultEditor};
 
 use clap::{Parser, Subcommand};
 use interpreter::inner_interpret;
-use types::{rc_mut_new, Pointer, RcMut, State, Syntax};
+use types::{rc_mut_new, Config, Pointer, RcMut, State, Syntax};
 
 mod interpreter;
 mod lexer;
@@ -36,6 +36,11 @@ enum SubcommandArg {
         /// path to the source file (optional)
         path: Option<String>,
     },
+    /// report every syntax error in a file without running it
+    Check {
+        /// path to the source file
+        path: String,
+    },
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
@@ -45,6 +50,15 @@ fn main() -> Result<(), Box<dyn Error>> {
             let _result = interpreter::interpret(&file_to_syntax(&PathBuf::from(path))?)?;
             // println!("{result:?}");
         }
+        SubcommandArg::Check { path } => {
+            let source = fs::read_to_string(path)?;
+            if let Err(errors) = parser::parse_recovering(&source, &Config::default()) {
+                for err in &errors {
+                    println!("Error: {err}");
+                }
+                return Err(format!("found {} errors", errors.len()).into());
+            }
+        }
         SubcommandArg::Repl { path } => {
             println!("\x1b[93mRepl - DreamBerd-rs\x1b[0m");
             //
@@ -56,7 +70,8 @@ fn main() -> Result<(), Box<dyn Error>> {
                     other => vec![other],
                 };
                 for statement in statements {
-                    inner_interpret(&statement, state.clone())?;
+                    inner_interpret(&statement, state.clone())
+                        .map_err(interpreter::stray_signal)?;
                 }
                 // println!("{result}");
                 // println!("{state:?}");
@@ -121,7 +136,8 @@ fn run_input(input: &str, context: RcMut<State>) -> Result<Pointer, Box<dyn Erro
     Ok(inner_interpret(
         &parser::parse(lexer::tokenize(&format!("{{{input}}}"))?)?,
         context,
-    )?)
+    )
+    .map_err(interpreter::stray_signal)?)
 }
 
 fn file_to_syntax(path: &Path) -> Result<Syntax, Box<dyn Error>> {
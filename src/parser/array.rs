@@ -0,0 +1,95 @@
+use crate::types::prelude::*;
+
+use super::{grouping, TokenStream};
+
+/// Parse the tokens between `[` and `]` into an array literal or a comprehension
+pub(super) fn parse_array(tokens: Vec<Token>) -> SResult<Syntax> {
+    let Some(ident_pos) = find_word(&tokens, "for", 0) else {
+        return parse_items(tokens);
+    };
+    let Some(iterable_pos) = find_word(&tokens, "in", ident_pos) else {
+        return Err(String::from("Expected `in` after `for` in comprehension"));
+    };
+    let condition_pos = find_word(&tokens, "if", iterable_pos);
+
+    let mut tokens = tokens;
+    let condition =
+        condition_pos.map(|condition_pos| tokens.split_off(condition_pos)[1..].to_vec());
+    let iterable = tokens.split_off(iterable_pos)[1..].to_vec();
+    let ident = tokens.split_off(ident_pos)[1..].to_vec();
+    let body = tokens;
+
+    let ident = match &ident[..] {
+        [Token::Space(_), Token::Ident(ident), Token::Space(_)] => ident.clone(),
+        other => {
+            return Err(format!(
+                "Expected an identifier between `for` and `in`; got `{other:?}`"
+            ))
+        }
+    };
+    Ok(Syntax::Comprehension(
+        Box::new(parse_single(body)?),
+        ident,
+        Box::new(parse_single(iterable)?),
+        condition.map(parse_single).transpose()?.map(Box::new),
+    ))
+}
+
+/// find a word like `for` that isn't nested in brackets and has whitespace after it
+fn find_word(tokens: &[Token], word: &str, start: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (idx, tok) in tokens.iter().enumerate().skip(start) {
+        match tok {
+            Token::LSquare | Token::LParen | Token::LSquirrely => depth += 1,
+            Token::RSquare | Token::RParen | Token::RSquirrely => depth = depth.saturating_sub(1),
+            Token::Ident(_) | Token::Keyword(_)
+                if depth == 0
+                    && is_word(tok, word)
+                    && matches!(tokens.get(idx + 1), Some(Token::Space(_))) =>
+            {
+                return Some(idx);
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn is_word(tok: &Token, word: &str) -> bool {
+    match tok {
+        Token::Ident(id) => id.as_ref() == word,
+        Token::Keyword(kw) => kw.to_string() == word,
+        _ => false,
+    }
+}
+
+fn parse_items(tokens: Vec<Token>) -> SResult<Syntax> {
+    let mut tokens = TokenStream::new(tokens);
+    let mut items = Vec::new();
+    loop {
+        super::consume_whitespace(&mut tokens);
+        match tokens.peek() {
+            None => break,
+            Some(Token::Comma) => {
+                tokens.next();
+            }
+            Some(Token::Ellipsis) => {
+                tokens.next();
+                items.push(Syntax::Spread(Box::new(grouping::parse_group(
+                    &mut tokens,
+                )?)));
+            }
+            Some(_) => items.push(grouping::parse_group(&mut tokens)?),
+        }
+    }
+    Ok(Syntax::Array(items))
+}
+
+fn parse_single(tokens: Vec<Token>) -> SResult<Syntax> {
+    let mut tokens = TokenStream::new(tokens);
+    let syn = grouping::parse_group(&mut tokens)?;
+    super::consume_whitespace(&mut tokens);
+    tokens.next().map_or(Ok(syn), |other| {
+        Err(format!("Unexpected token in comprehension: `{other:?}`"))
+    })
+}
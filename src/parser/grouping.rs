@@ -2,16 +2,16 @@ use std::iter::Peekable;
 
 use crate::types::prelude::*;
 
-use super::{consume_whitespace, inner_parse};
+use super::{consume_whitespace, inner_parse, TokenStream};
 
 #[derive(Debug, Clone)]
 enum GroupThingieEnum {
-    Syntax(Syntax, u8),
-    Operation(Operation, u8),
-    Unary(UnaryOperation, u8),
+    Syntax(Syntax, u32),
+    Operation(Operation, u32),
+    Unary(UnaryOperation, u32),
 }
 
-pub(super) fn parse_group<T: Iterator<Item = Token>>(tokens: &mut Peekable<T>) -> SResult<Syntax> {
+pub(super) fn parse_group(tokens: &mut TokenStream) -> SResult<Syntax> {
     let new_toks = fancify_toks(tokens)?;
     let max_spc = new_toks
         .iter()
@@ -28,13 +28,23 @@ pub(super) fn parse_group<T: Iterator<Item = Token>>(tokens: &mut Peekable<T>) -
     inner_parse_group_better(&mut new_toks.into_iter().rev().peekable(), max_spc + 1)
 }
 
-fn fancify_toks<T: Iterator<Item = Token>>(
-    tokens: &mut Peekable<T>,
-) -> SResult<Vec<GroupThingieEnum>> {
+fn fancify_toks(tokens: &mut TokenStream) -> SResult<Vec<GroupThingieEnum>> {
     let mut toks = Vec::new();
     loop {
         let mut whitespace = consume_whitespace(tokens);
         match tokens.peek() {
+            Some(Token::TackTack | Token::PlusPlus) if expects_operand(&toks) => {
+                let unary = if tokens.next() == Some(Token::PlusPlus) {
+                    UnaryOperation::PreIncrement
+                } else {
+                    UnaryOperation::PreDecrement
+                };
+                let operand = prefix_operand(tokens)?;
+                toks.push(GroupThingieEnum::Syntax(
+                    Syntax::UnaryOperation(unary, Box::new(operand)),
+                    whitespace,
+                ));
+            }
             Some(Token::TackTack) => {
                 tokens.next();
                 toks.push(GroupThingieEnum::Unary(
@@ -49,6 +59,32 @@ fn fancify_toks<T: Iterator<Item = Token>>(
                     whitespace,
                 ));
             }
+            // `;` is for negating, but `-inf` is too well-known to leave out
+            Some(Token::Tack) if expects_operand(&toks) => {
+                tokens.next();
+                let Some(Token::Ident(ident)) =
+                    tokens.next_if(|tok| *tok == Token::Ident("inf".into()))
+                else {
+                    return Err(String::from(
+                        "`-` can only negate `inf`; use `;` to negate anything else",
+                    ));
+                };
+                toks.push(GroupThingieEnum::Syntax(
+                    Syntax::UnaryOperation(UnaryOperation::Negate, Box::new(Syntax::Ident(ident))),
+                    whitespace,
+                ));
+            }
+            // `??` with whitespace before it is null-coalescing; attached to an expression, it ends a debug statement
+            Some(Token::Question(2)) if whitespace > 0 => {
+                tokens.next();
+                whitespace += consume_whitespace(tokens);
+                toks.push(GroupThingieEnum::Operation(Operation::Coalesce, whitespace));
+            }
+            // `!` where an operand is expected is a logical not; anywhere else, it ends the statement
+            Some(Token::Bang(_)) if expects_operand(&toks) => {
+                let inner = inner_parse(tokens)?;
+                toks.push(GroupThingieEnum::Syntax(inner, whitespace));
+            }
             Some(
                 Token::RParen
                 | Token::Bang(_)
@@ -77,9 +113,28 @@ fn fancify_toks<T: Iterator<Item = Token>>(
     Ok(toks)
 }
 
+/// check if the next token would be an operand rather than something applied to the previous one
+const fn expects_operand(toks: &[GroupThingieEnum]) -> bool {
+    matches!(toks.last(), None | Some(GroupThingieEnum::Operation(..)))
+}
+
+/// the operand of a prefix `++` or `--` is a value, optionally followed by unspaced `.member` accesses
+fn prefix_operand(tokens: &mut TokenStream) -> SResult<Syntax> {
+    let mut operand = inner_parse(tokens)?;
+    while tokens.peek() == Some(&Token::Dot) {
+        tokens.next();
+        operand = Syntax::Operation(
+            Box::new(operand),
+            Operation::Dot,
+            Box::new(inner_parse(tokens)?),
+        );
+    }
+    Ok(operand)
+}
+
 fn inner_parse_group_better<T: Iterator<Item = GroupThingieEnum>>(
     tokens: &mut Peekable<T>,
-    spacing: u8,
+    spacing: u32,
 ) -> SResult<Syntax> {
     if spacing == 0 {
         return match tokens.next() {
@@ -114,20 +169,69 @@ fn inner_parse_group_better<T: Iterator<Item = GroupThingieEnum>>(
     }?;
     match tokens.peek() {
         Some(GroupThingieEnum::Syntax(_, spc)) if *spc <= spacing => {
-            let Some(GroupThingieEnum::Syntax(func, _)) = tokens.next() else {unreachable!()};
+            let Some(GroupThingieEnum::Syntax(func, _)) = tokens.next() else {
+                unreachable!()
+            };
             let args = match starter_val {
                 Syntax::Block(args) => args,
                 other => vec![other],
             };
             Ok(Syntax::UnaryOperation(
                 UnaryOperation::Call(args),
-                Box::new(func),
+                Box::new(method_receiver(tokens, func)?),
             ))
         }
         _ => Ok(starter_val),
     }
 }
 
+/// If a called function is right after an unspaced `.`, it's a method like `3.14.sqrt()`. The receiver is the unspaced
+/// chain of `.`s before it, so it can be a whole number like `3.14` or a member like `obj.inner`
+fn method_receiver<T: Iterator<Item = GroupThingieEnum>>(
+    tokens: &mut Peekable<T>,
+    func: Syntax,
+) -> SResult<Syntax> {
+    // tokens come in backwards, so the receiver's parts do too
+    let mut parts = Vec::new();
+    while matches!(
+        tokens.peek(),
+        Some(GroupThingieEnum::Operation(Operation::Dot, 0))
+    ) {
+        tokens.next();
+        let Some(GroupThingieEnum::Syntax(part, _)) = tokens.next() else {
+            return Err(String::from("Expected a value before `.`"));
+        };
+        // a call right before the `.`, like the first one in `16.sqrt().sqrt()`, is the rest of the receiver
+        if let Some(GroupThingieEnum::Syntax(_, 0)) = tokens.peek() {
+            let Some(GroupThingieEnum::Syntax(func, _)) = tokens.next() else {
+                unreachable!()
+            };
+            let args = match part {
+                Syntax::Block(args) => args,
+                other => vec![other],
+            };
+            parts.push(Syntax::UnaryOperation(
+                UnaryOperation::Call(args),
+                Box::new(method_receiver(tokens, func)?),
+            ));
+            break;
+        }
+        parts.push(part);
+    }
+    let mut parts = parts.into_iter().rev();
+    let Some(first) = parts.next() else {
+        return Ok(func);
+    };
+    let receiver = parts.fold(first, |lhs, rhs| {
+        Syntax::Operation(Box::new(lhs), Operation::Dot, Box::new(rhs))
+    });
+    Ok(Syntax::Operation(
+        Box::new(receiver),
+        Operation::Dot,
+        Box::new(func),
+    ))
+}
+
 /// if `op` is `->`, try to make it into a function
 fn make_operation(left: Syntax, op: Operation, right: Syntax) -> SResult<Syntax> {
     if op == Operation::Arrow {
@@ -1,11 +1,23 @@
-use std::iter::Peekable;
+use std::rc::Rc;
 
 use crate::types::prelude::*;
 
+mod array;
 mod grouping;
+mod object;
+mod print;
+mod stream;
+
+pub use print::{write_function, write_source};
+pub use stream::TokenStream;
 
 pub fn parse(tokens: Vec<Token>) -> SResult<Syntax> {
-    let mut tokens = tokens.into_iter().peekable();
+    let mut tokens = TokenStream::new(
+        tokens
+            .into_iter()
+            .filter(|tok| !matches!(tok, Token::Comment(_)))
+            .collect(),
+    );
     let mut syntax = Vec::new();
     while tokens.peek().is_some() {
         syntax.push(grouping::parse_group(&mut tokens)?);
@@ -13,7 +25,64 @@ pub fn parse(tokens: Vec<Token>) -> SResult<Syntax> {
     Ok(optimize(Syntax::Block(syntax)))
 }
 
-fn inner_parse<T: Iterator<Item = Token>>(tokens: &mut Peekable<T>) -> SResult<Syntax> {
+/// Parse the statements of a whole file, reporting every statement that doesn't parse instead of stopping at the first
+/// one. When a statement has an error, it's skipped up to the `!` or `?` that ends it (not counting any inside of
+/// brackets), and parsing picks up again after that. Errors say the line and column of the token where they happened
+pub fn parse_recovering(source: &str, config: &Config) -> Result<Syntax, Vec<String>> {
+    let (tokens, starts): (Vec<_>, Vec<_>) = crate::lexer::tokenize_with_starts(source, config)
+        .map_err(|err| vec![err])?
+        .into_iter()
+        .filter(|(tok, _)| !matches!(tok, Token::Comment(_)))
+        .unzip();
+    let total = tokens.len();
+    let at = |remaining: usize| {
+        let idx = starts
+            .get(total - remaining)
+            .copied()
+            .unwrap_or_else(|| source.chars().count());
+        crate::lexer::position(source, idx)
+    };
+    let mut tokens = TokenStream::new(tokens);
+    let mut syntax = Vec::new();
+    let mut errors = Vec::new();
+    while let Some(tok) = tokens.peek() {
+        if let Token::Space(_) = tok {
+            tokens.next();
+            continue;
+        }
+        let start = tokens.position();
+        match grouping::parse_group(&mut tokens) {
+            Ok(statement) => syntax.push(consume_bang(statement, &mut tokens)),
+            Err(err) => {
+                let (line, column) = at(tokens.remaining());
+                errors.push(format!("{err} at {line}:{column}"));
+                tokens.rewind(start);
+                skip_statement(&mut tokens);
+            }
+        }
+    }
+    if errors.is_empty() {
+        Ok(optimize(Syntax::Block(syntax)))
+    } else {
+        Err(errors)
+    }
+}
+
+/// Skip past the end of the next statement, which is the next `!` or `?` that isn't inside of brackets
+fn skip_statement(tokens: &mut TokenStream) {
+    let mut depth = 0usize;
+    for tok in tokens.by_ref() {
+        match tok {
+            Token::LParen | Token::LSquare | Token::LSquirrely => depth += 1,
+            Token::RParen | Token::RSquare | Token::RSquirrely => depth = depth.saturating_sub(1),
+            Token::Bang(_) | Token::Question(_) if depth == 0 => break,
+            _ => {}
+        }
+    }
+    consume_whitespace(tokens);
+}
+
+fn inner_parse(tokens: &mut TokenStream) -> SResult<Syntax> {
     // println!("{:?}", tokens.peek());
     match tokens.next() {
         Some(Token::String(str)) => Ok(Syntax::String(str)),
@@ -21,43 +90,35 @@ fn inner_parse<T: Iterator<Item = Token>>(tokens: &mut Peekable<T>) -> SResult<S
             UnaryOperation::Negate,
             Box::new(inner_parse(tokens)?),
         )),
-        Some(Token::Ident(id)) => {
-            if id.as_ref() == "const" || id.as_ref() == "var" {
-                consume_whitespace(tokens);
-                declare(tokens, &id)
-            } else {
-                match tokens.peek() {
-                    Some(Token::Colon) => {
-                        tokens.next();
-                        consume_whitespace(tokens);
-                        get_type(tokens)?;
-                        Ok(Syntax::Ident(id))
-                    }
-                    // get the value of the variable
-                    _ => Ok(Syntax::Ident(id)),
-                }
-            }
+        Some(Token::Bang(count)) => Ok(Syntax::UnaryOperation(
+            UnaryOperation::Not(count),
+            Box::new(inner_parse(tokens)?),
+        )),
+        Some(Token::Keyword(kw @ (Keyword::Const | Keyword::Var))) => {
+            consume_whitespace(tokens);
+            declare(tokens, kw)
         }
+        // keywords are still looked up by name, so they can be deleted or redefined
+        Some(Token::Keyword(kw)) => ident(tokens, kw.to_string().into()),
+        Some(Token::Ident(id)) => ident(tokens, id),
         Some(Token::LSquirrely) => {
+            let inner = collect_until(tokens, &Token::RSquirrely)?;
+            if object::is_object(&inner) {
+                return object::parse_object(inner);
+            }
+            let mut tokens = TokenStream::new(inner);
             let mut statements_buf = Vec::new();
             while let Some(tok) = tokens.peek() {
-                match tok {
-                    Token::RSquirrely => break,
-                    Token::Space(_) => {
-                        tokens.next();
-                        continue;
-                    }
-                    _ => {}
+                if let Token::Space(_) = tok {
+                    tokens.next();
+                    continue;
                 }
-                let inner = grouping::parse_group::<T>(tokens)?;
-                statements_buf.push(consume_bang(inner, tokens));
-            }
-            if tokens.next() == Some(Token::RSquirrely) {
-                Ok(Syntax::Block(statements_buf))
-            } else {
-                Err(String::from("Expected `}`"))
+                let inner = grouping::parse_group(&mut tokens)?;
+                statements_buf.push(consume_bang(inner, &mut tokens));
             }
+            Ok(Syntax::Block(statements_buf))
         }
+        Some(Token::LSquare) => array::parse_array(collect_until(tokens, &Token::RSquare)?),
         Some(Token::Space(_)) => inner_parse(tokens),
         Some(Token::LParen) => {
             let val = get_tuple(tokens)?;
@@ -72,16 +133,37 @@ fn inner_parse<T: Iterator<Item = Token>>(tokens: &mut Peekable<T>) -> SResult<S
     }
 }
 
-fn consume_whitespace<T: Iterator<Item = Token>>(tokens: &mut Peekable<T>) -> u8 {
-    let mut sp = 0;
+/// Collect the tokens up to the bracket `end` that closes the one we just saw, not counting nested brackets
+fn collect_until(tokens: &mut TokenStream, end: &Token) -> SResult<Vec<Token>> {
+    let mut inner = Vec::new();
+    let mut depth = 0usize;
+    loop {
+        match tokens.next() {
+            Some(tok) if depth == 0 && tok == *end => return Ok(inner),
+            Some(tok @ (Token::LSquare | Token::LParen | Token::LSquirrely)) => {
+                depth += 1;
+                inner.push(tok);
+            }
+            Some(tok @ (Token::RSquare | Token::RParen | Token::RSquirrely)) if depth > 0 => {
+                depth -= 1;
+                inner.push(tok);
+            }
+            Some(tok) => inner.push(tok),
+            None => return Err(format!("Expected `{end}`")),
+        }
+    }
+}
+
+fn consume_whitespace(tokens: &mut TokenStream) -> u32 {
+    let mut sp: u32 = 0;
     while let Some(&Token::Space(s)) = tokens.peek() {
         tokens.next();
-        sp += s;
+        sp = sp.saturating_add(s);
     }
     sp
 }
 
-fn consume_bang<T: Iterator<Item = Token>>(syn: Syntax, tokens: &mut Peekable<T>) -> Syntax {
+fn consume_bang(syn: Syntax, tokens: &mut TokenStream) -> Syntax {
     match tokens.peek() {
         Some(&Token::Bang(q)) => {
             tokens.next();
@@ -95,26 +177,38 @@ fn consume_bang<T: Iterator<Item = Token>>(syn: Syntax, tokens: &mut Peekable<T>
     }
 }
 
-fn declare<T: Iterator<Item = Token>>(tokens: &mut Peekable<T>, id: &str) -> SResult<Syntax> {
-    let Some(Token::Ident(second)) = tokens.next() else {
-                    return Err(String::from("Expected `const` or `var` after `{id}`"))
-                };
-    let var_type = match (id, second.as_ref()) {
-        ("var", "var") => VarType::VarVar,
-        ("var", "const") => VarType::VarConst,
-        ("const", "var") => VarType::ConstVar,
-        ("const", "const") => VarType::ConstConst,
-        ("var" | "const", _) => {
+fn ident(tokens: &mut TokenStream, id: Rc<str>) -> SResult<Syntax> {
+    // consume a type annotation
+    if tokens.peek() == Some(&Token::Colon) {
+        tokens.next();
+        consume_whitespace(tokens);
+        get_type(tokens)?;
+    }
+    Ok(Syntax::Ident(id))
+}
+
+fn declare(tokens: &mut TokenStream, id: Keyword) -> SResult<Syntax> {
+    let second = match tokens.next() {
+        Some(Token::Keyword(second @ (Keyword::Const | Keyword::Var))) => second,
+        other => {
             return Err(format!(
-                "Expected `const` or `var` after `{id}`, not `{second}`"
+                "Expected `const` or `var` after `{id}`, not `{other:?}`"
             ))
         }
+    };
+    let var_type = match (id, second) {
+        (Keyword::Var, Keyword::Var) => VarType::VarVar,
+        (Keyword::Var, Keyword::Const) => VarType::VarConst,
+        (Keyword::Const, Keyword::Var) => VarType::ConstVar,
+        (Keyword::Const, Keyword::Const) => VarType::ConstConst,
         _ => unreachable!(),
     };
     consume_whitespace(tokens);
-    let Some(Token::Ident(varname)) = tokens.next() else {
-                    return Err(format!("Expected a variable name after `{id} {second}`"))
-                };
+    let varname = match tokens.next() {
+        Some(Token::Ident(varname)) => varname,
+        Some(Token::Keyword(kw)) => kw.to_string().into(),
+        _ => return Err(format!("Expected a variable name after `{id} {second}`")),
+    };
     consume_whitespace(tokens);
     // get a lifetime
     let lifetime = match tokens.peek() {
@@ -125,15 +219,7 @@ fn declare<T: Iterator<Item = Token>>(tokens: &mut Peekable<T>, id: &str) -> SRe
                     let lt = Lifetime::Ticks(ident.parse().map_err(|err| {
                         format!("Expected integer lifetime; got `{ident}`; {err}")
                     })?);
-                    match tokens.next() {
-                        Some(Token::RCaret) => {}
-                        Some(other) => {
-                            return Err(format!(
-                                "Expected `>` after lifetime value; got `{other:?}`"
-                            ))
-                        }
-                        None => return Err(String::from("Unexpected EOF")),
-                    }
+                    tokens.expect(&Token::RCaret, "after lifetime value")?;
                     lt
                 }
                 Some(other) => return Err(format!("Expected integer lifetime; got `{other:?}`")),
@@ -154,7 +240,7 @@ fn declare<T: Iterator<Item = Token>>(tokens: &mut Peekable<T>, id: &str) -> SRe
         Some(Token::Bang(_)) => Syntax::Ident(String::new().into()),
         Some(Token::Equal(1)) => {
             consume_whitespace(tokens);
-            grouping::parse_group::<T>(tokens)?
+            grouping::parse_group(tokens)?
         }
         other => {
             return Err(format!(
@@ -168,7 +254,7 @@ fn declare<T: Iterator<Item = Token>>(tokens: &mut Peekable<T>, id: &str) -> SRe
     ))
 }
 
-fn get_tuple<T: Iterator<Item = Token>>(tokens: &mut Peekable<T>) -> SResult<Vec<Syntax>> {
+fn get_tuple(tokens: &mut TokenStream) -> SResult<Vec<Syntax>> {
     let mut args_buf = Vec::new();
     while let Some(tok) = tokens.peek() {
         match tok {
@@ -180,13 +266,13 @@ fn get_tuple<T: Iterator<Item = Token>>(tokens: &mut Peekable<T>) -> SResult<Vec
                 tokens.next();
                 break;
             }
-            _ => args_buf.push(grouping::parse_group::<T>(tokens)?),
+            _ => args_buf.push(grouping::parse_group(tokens)?),
         }
     }
     Ok(args_buf)
 }
 
-fn get_type<T: Iterator<Item = Token>>(tokens: &mut Peekable<T>) -> SResult<()> {
+fn get_type(tokens: &mut TokenStream) -> SResult<()> {
     match tokens.next() {
         Some(Token::Ident(_)) => {}
         other => return Err(format!("Expected a type after `:`; got `{other:?}`")),
@@ -195,9 +281,7 @@ fn get_type<T: Iterator<Item = Token>>(tokens: &mut Peekable<T>) -> SResult<()>
     match tokens.peek() {
         Some(Token::LSquare) => {
             tokens.next();
-            let Some(Token::RSquare) = tokens.next() else {
-                return Err(String::from("Expected `]` after `[` in type definition"))
-            };
+            tokens.expect(&Token::RSquare, "after `[` in type definition")?;
         }
         Some(Token::LCaret) => {
             tokens.next();
@@ -207,9 +291,7 @@ fn get_type<T: Iterator<Item = Token>>(tokens: &mut Peekable<T>) -> SResult<()>
                 get_type(tokens)?;
                 consume_whitespace(tokens);
             }
-            let Some(Token::RCaret) = tokens.next() else {
-                return Err(String::from("Missing `>` in type definition"))
-            };
+            tokens.expect(&Token::RCaret, "at the end of type definition")?;
         }
         _ => {}
     }
@@ -228,14 +310,20 @@ fn optimize(syn: Syntax) -> Syntax {
         ),
         Syntax::Operation(lhs, Operation::Dot, rhs)
             if 'guard: {
-                let Syntax::Ident(ref lhs) = *lhs else { break 'guard false };
-                let Syntax::Ident(ref rhs) = *rhs else { break 'guard false };
+                let Syntax::Ident(ref lhs) = *lhs else {
+                    break 'guard false;
+                };
+                let Syntax::Ident(ref rhs) = *rhs else {
+                    break 'guard false;
+                };
                 format!("{lhs}.{rhs}").parse::<f64>().is_ok()
             } =>
         {
-            let Syntax::Ident(lhs) = *lhs else {panic!()};
-            let Syntax::Ident(rhs) = *rhs else {panic!()};
-            let Ok(float) = format!("{lhs}.{rhs}").parse::<f32>() else { panic!()};
+            let Syntax::Ident(lhs) = *lhs else { panic!() };
+            let Syntax::Ident(rhs) = *rhs else { panic!() };
+            let Ok(float) = format!("{lhs}.{rhs}").parse::<f32>() else {
+                panic!()
+            };
             Syntax::Ident(format!("{float}").into())
         }
         Syntax::Operation(lhs, op, rhs) => {
@@ -263,6 +351,20 @@ fn optimize(syn: Syntax) -> Syntax {
         Syntax::Statement(is_debug, inner, lvl) => {
             Syntax::Statement(is_debug, Box::new(optimize(*inner)), lvl)
         }
-        basic @ (Syntax::Ident(_) | Syntax::String(_)) => basic,
+        Syntax::Array(items) => Syntax::Array(items.into_iter().map(optimize).collect()),
+        Syntax::Spread(value) => Syntax::Spread(Box::new(optimize(*value))),
+        Syntax::Object(entries) => Syntax::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key, optimize(value)))
+                .collect(),
+        ),
+        Syntax::Comprehension(body, ident, iterable, condition) => Syntax::Comprehension(
+            Box::new(optimize(*body)),
+            ident,
+            Box::new(optimize(*iterable)),
+            condition.map(|condition| Box::new(optimize(*condition))),
+        ),
+        basic @ (Syntax::Ident(_) | Syntax::String(_) | Syntax::Literal(_)) => basic,
     }
 }
@@ -0,0 +1,47 @@
+use std::rc::Rc;
+
+use crate::types::prelude::*;
+
+use super::{grouping, TokenStream};
+
+/// Check if the tokens between `{` and `}` start with `key:` or `...`, which makes them an object literal instead of a block
+pub(super) fn is_object(tokens: &[Token]) -> bool {
+    let mut tokens = tokens.iter().filter(|tok| !matches!(tok, Token::Space(_)));
+    matches!(
+        (tokens.next(), tokens.next()),
+        (Some(Token::Ellipsis), _)
+            | (
+                Some(Token::Ident(_) | Token::Keyword(_) | Token::String(_)),
+                Some(Token::Colon)
+            )
+    )
+}
+
+/// Parse the tokens between `{` and `}` into an object literal like `{name: "Ava", ...stats}`. Keys always become strings
+pub(super) fn parse_object(tokens: Vec<Token>) -> SResult<Syntax> {
+    let mut tokens = TokenStream::new(tokens);
+    let mut entries = Vec::new();
+    loop {
+        super::consume_whitespace(&mut tokens);
+        let key: Rc<str> = match tokens.next() {
+            None => break,
+            Some(Token::Comma) => continue,
+            Some(Token::Ellipsis) => {
+                entries.push((None, grouping::parse_group(&mut tokens)?));
+                continue;
+            }
+            Some(Token::Ident(ident)) => ident,
+            Some(Token::Keyword(kw)) => kw.to_string().into(),
+            Some(Token::String(segments)) => match &segments[..] {
+                [] => "".into(),
+                [StringSegment::String(str)] => str.clone(),
+                _ => return Err(String::from("Object keys can't be interpolated")),
+            },
+            Some(other) => return Err(format!("Expected an object key; got `{other:?}`")),
+        };
+        super::consume_whitespace(&mut tokens);
+        tokens.expect(&Token::Colon, &format!("after object key `{key}`"))?;
+        entries.push((Some(key), grouping::parse_group(&mut tokens)?));
+    }
+    Ok(Syntax::Object(entries))
+}
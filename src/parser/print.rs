@@ -0,0 +1,174 @@
+use std::{fmt, rc::Rc};
+
+use crate::types::prelude::*;
+
+/// Write syntax back out as source code that's meant to be read. Blocks get one statement per line, and nested
+/// operations get parentheses so they still group the same way when they're parsed again. This is what `{:#}` does
+pub fn write_source(f: &mut fmt::Formatter<'_>, syntax: &Syntax) -> fmt::Result {
+    write_syntax(f, syntax, 0)
+}
+
+/// Write a function the way it would be declared, like `(a, b) -> {a + b}`. Bodies that aren't blocks still get
+/// braces around them
+pub fn write_function(f: &mut fmt::Formatter<'_>, args: &[Rc<str>], body: &Syntax) -> fmt::Result {
+    write_function_at(f, args, body, 0)
+}
+
+fn write_syntax(f: &mut fmt::Formatter<'_>, syntax: &Syntax, indent: usize) -> fmt::Result {
+    match syntax {
+        Syntax::Statement(is_debug, content, count) => {
+            write_syntax(f, content, indent)?;
+            write!(
+                f,
+                "{}",
+                if *is_debug { "?" } else { "!" }.repeat(*count as usize)
+            )
+        }
+        Syntax::Block(statements) if statements.is_empty() => write!(f, "{{}}"),
+        Syntax::Block(statements) => {
+            writeln!(f, "{{")?;
+            for statement in statements {
+                write!(f, "{}", INDENT.repeat(indent + 1))?;
+                write_syntax(f, statement, indent + 1)?;
+                writeln!(f)?;
+            }
+            write!(f, "{}}}", INDENT.repeat(indent))
+        }
+        Syntax::String(_) | Syntax::Ident(_) => write!(f, "{syntax}"),
+        Syntax::Literal(value) => write!(f, "{value:?}"),
+        Syntax::Array(items) => {
+            write!(f, "[")?;
+            write_list(f, items, indent)?;
+            write!(f, "]")
+        }
+        Syntax::Object(entries) => {
+            write!(f, "{{")?;
+            for (idx, (key, value)) in entries.iter().enumerate() {
+                if idx > 0 {
+                    write!(f, ", ")?;
+                }
+                match key {
+                    Some(key) => write!(f, "{key}: ")?,
+                    None => write!(f, "...")?,
+                }
+                write_syntax(f, value, indent)?;
+            }
+            write!(f, "}}")
+        }
+        Syntax::Spread(value) => {
+            write!(f, "...")?;
+            write_operand(f, value, indent)
+        }
+        Syntax::Comprehension(body, name, iterable, condition) => {
+            write!(f, "[")?;
+            write_syntax(f, body, indent)?;
+            write!(f, " for {name} in ")?;
+            write_syntax(f, iterable, indent)?;
+            if let Some(condition) = condition {
+                write!(f, " if ")?;
+                write_syntax(f, condition, indent)?;
+            }
+            write!(f, "]")
+        }
+        Syntax::Declare(var_type, name, lifetime, value) => {
+            write!(f, "{var_type} {name}{lifetime} = ")?;
+            write_syntax(f, value, indent)
+        }
+        Syntax::Operation(lhs, Operation::Dot, rhs) => {
+            write_operand(f, lhs, indent)?;
+            write!(f, ".")?;
+            write_operand(f, rhs, indent)
+        }
+        Syntax::Operation(lhs, op, rhs) => {
+            write_operand(f, lhs, indent)?;
+            write!(f, " {op} ")?;
+            write_operand(f, rhs, indent)
+        }
+        Syntax::UnaryOperation(op, operand) => write_unary(f, op, operand, indent),
+        Syntax::Function(args, body) => write_function_at(f, args, body, indent),
+    }
+}
+
+fn write_unary(
+    f: &mut fmt::Formatter<'_>,
+    op: &UnaryOperation,
+    operand: &Syntax,
+    indent: usize,
+) -> fmt::Result {
+    match op {
+        UnaryOperation::Call(args) => {
+            write_operand(f, operand, indent)?;
+            write!(f, "(")?;
+            write_list(f, args, indent)?;
+            write!(f, ")")
+        }
+        UnaryOperation::Increment => {
+            write_operand(f, operand, indent)?;
+            write!(f, "++")
+        }
+        UnaryOperation::Decrement => {
+            write_operand(f, operand, indent)?;
+            write!(f, "--")
+        }
+        UnaryOperation::PreIncrement => {
+            write!(f, "++")?;
+            write_operand(f, operand, indent)
+        }
+        UnaryOperation::PreDecrement => {
+            write!(f, "--")?;
+            write_operand(f, operand, indent)
+        }
+        UnaryOperation::Negate => {
+            write!(f, ";")?;
+            write_operand(f, operand, indent)
+        }
+        UnaryOperation::Not(count) => {
+            write!(f, "{}", "!".repeat(*count as usize))?;
+            write_operand(f, operand, indent)
+        }
+    }
+}
+
+fn write_function_at(
+    f: &mut fmt::Formatter<'_>,
+    args: &[Rc<str>],
+    body: &Syntax,
+    indent: usize,
+) -> fmt::Result {
+    write!(f, "({}) -> ", args.join(", "))?;
+    if matches!(body, Syntax::Block(_)) {
+        write_syntax(f, body, indent)
+    } else {
+        write!(f, "{{")?;
+        write_syntax(f, body, indent)?;
+        write!(f, "}}")
+    }
+}
+
+/// Write something that's part of a bigger expression, in parentheses if it could group differently without them
+fn write_operand(f: &mut fmt::Formatter<'_>, syntax: &Syntax, indent: usize) -> fmt::Result {
+    let needs_parens = match syntax {
+        Syntax::Operation(_, op, _) => *op != Operation::Dot,
+        Syntax::Function(..) | Syntax::UnaryOperation(UnaryOperation::Not(_), _) => true,
+        _ => false,
+    };
+    if needs_parens {
+        write!(f, "(")?;
+        write_syntax(f, syntax, indent)?;
+        write!(f, ")")
+    } else {
+        write_syntax(f, syntax, indent)
+    }
+}
+
+fn write_list(f: &mut fmt::Formatter<'_>, items: &[Syntax], indent: usize) -> fmt::Result {
+    for (idx, item) in items.iter().enumerate() {
+        if idx > 0 {
+            write!(f, ", ")?;
+        }
+        write_syntax(f, item, indent)?;
+    }
+    Ok(())
+}
+
+const INDENT: &str = "    ";
@@ -0,0 +1,66 @@
+use crate::types::prelude::*;
+
+/// The tokens the parser hasn't gotten to yet, with as much lookahead as it needs
+#[derive(Debug, Clone, Default)]
+pub struct TokenStream {
+    /// Every token, including the ones that have been read, so the stream can go back to them
+    tokens: Vec<Token>,
+    /// The index of the next token
+    pos: usize,
+}
+
+impl TokenStream {
+    pub const fn new(tokens: Vec<Token>) -> Self {
+        Self { tokens, pos: 0 }
+    }
+
+    /// How many tokens are left
+    pub const fn remaining(&self) -> usize {
+        self.tokens.len() - self.pos
+    }
+
+    /// Where the stream is now, to go back to later with `rewind`
+    pub const fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Go back to a `position`, so everything after it can be read again
+    pub const fn rewind(&mut self, pos: usize) {
+        self.pos = pos;
+    }
+
+    pub fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    /// Take the next token, but only if `func` likes it
+    pub fn next_if(&mut self, func: impl FnOnce(&Token) -> bool) -> Option<Token> {
+        if self.peek().is_some_and(func) {
+            self.next()
+        } else {
+            None
+        }
+    }
+
+    /// Take the next token, which has to be `expected`. The `context` says where it was expected, like
+    /// ``"after object key `a`"``
+    pub fn expect(&mut self, expected: &Token, context: &str) -> SResult<()> {
+        match self.next() {
+            Some(tok) if tok == *expected => Ok(()),
+            Some(other) => Err(format!("Expected `{expected}` {context}; got `{other}`")),
+            None => Err(format!(
+                "Expected `{expected}` {context}; got the end of the file"
+            )),
+        }
+    }
+}
+
+impl Iterator for TokenStream {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        let tok = self.tokens.get(self.pos)?.clone();
+        self.pos += 1;
+        Some(tok)
+    }
+}
@@ -11,6 +11,18 @@ fn eval<T: Display>(src: T) -> SResult<Value> {
     )
 }
 
+/// Like `eval`, but `setup` gets to change the state first, like turning on settings or registering builtins
+fn eval_with<F: FnOnce(&State), T: Display>(setup: F, src: T) -> SResult<Value> {
+    let state = State::new();
+    setup(&state);
+    Ok(crate::interpreter::inner_interpret(
+        &crate::parser::parse(crate::lexer::tokenize(&format!("{{{src}}}"))?)?,
+        rc_mut_new(state),
+    )
+    .map_err(crate::interpreter::stray_signal)?
+    .clone_inner())
+}
+
 macro_rules! assert_eq_db {
     ($lhs: expr, $rhs: expr) => {
         let ltoks = crate::lexer::tokenize(&format!("{{{}}}", $lhs)).unwrap();
@@ -87,6 +99,15 @@ fn op_assign() {
         "{const var msg = 'i did '! msg += ;msg! msg}",
         "`i did  did i`"
     );
+    assert_eq_db!("const var x = 10! x -= 4! x", "6");
+    assert_eq_db!("const var x = 3! x *= 4! x", "12");
+    assert_eq_db!("const var x = `ab`! x *= 2! x", "`abab`");
+    assert_eq_db!("const var x = 9! x /= 2! x", "4.5");
+    assert_eq_db!("const var x = 9! x /= 0! x", "undefined");
+    assert_eq_db!("const var x = 9! x %= 4! x", "1");
+    assert_eq_db!("const var x = 9! x %= 0! x", "undefined");
+    assert_eq_db!("var const x = 9! x %= 4! x", "9");
+    assert_eq_db!("const var x = 1! const var n = next x! x %= 1! n", "0");
 }
 
 #[test]
@@ -154,5 +175,2725 @@ fn eval_tests() {
     assert_eq_db!("eval(2)", "2");
     assert_eq_db!("const var x = 1! x += 2! eval(`x`)", "3");
     assert_eq_db!("const var x = 1! x += 2! eval(x)", "3");
-    assert_eq_db!("const const x = `'Hello, World!'`! eval(x)", "`Hello, World!`");
+    assert_eq_db!(
+        "const const x = `'Hello, World!'`! eval(x)",
+        "`Hello, World!`"
+    );
+}
+
+#[test]
+fn null_coalescing() {
+    assert_eq_db!("3 ?? 5", "3");
+    assert_eq_db!("undefined ?? 5", "5");
+    assert_eq_db!("3/0 ?? `fallback`", "`fallback`");
+    assert_eq_db!("undefined ?? undefined ?? 7", "7");
+    assert_eq_db!("undefined ?? 6 ?? 7", "6");
+    assert_eq_db!("const var x = 1! x ?? eval(`x = 2`)! x", "1");
+}
+
+#[test]
+fn logical_not() {
+    assert_eq_db!("!true", "false");
+    assert_eq_db!("!!3", "true");
+    assert_eq_db!("!!!3", "false");
+    assert_eq_db!("!maybe", "maybe");
+    assert_eq_db!("!!maybe", "maybe");
+    assert_eq_db!("!``", "true");
+    assert_eq_db!("const const x = !0! x", "true");
+}
+
+#[test]
+fn increment_decrement() {
+    assert_eq_db!("const var x = 1! x++", "1");
+    assert_eq_db!("const var x = 1! x++! x", "2");
+    assert_eq_db!("const var x = 1! ++x", "2");
+    assert_eq_db!("const var x = 1! ++x! x", "2");
+    assert_eq_db!("const var x = 5! x--", "5");
+    assert_eq_db!("const var x = 5! --x", "4");
+    assert_eq_db!("const var x = 5! x--! --x! x", "3");
+    assert_eq_db!("const var o = {}! o.n = 1! o.n++! o.n", "2");
+    assert_eq_db!("const var o = {}! o.n = 1! ++o.n", "2");
+    assert_eq_db!("const var x = 1! const var n = next x! x++! n", "2");
+    assert_eq_db!("const const x = 1! x++! x", "1");
+    assert_eq_db!("const var s = `a`! s++! s", "`a1`");
+    assert_eq_db!("const var s = `a`! s--! s", "undefined");
+}
+
+#[test]
+fn empty_and_default() {
+    let mut obj = ObjectMap::new();
+    obj.insert(Value::from("a"), Pointer::from(1.0));
+    let cases = [
+        (Value::empty_object(), true, true),
+        (Value::Object(obj), false, false),
+        (Value::from(""), true, false),
+        (Value::from("a"), false, false),
+        (Value::from(0.0), false, false),
+        (Value::from(false), false, false),
+        (Value::Boolean(Boolean::Maybe), false, false),
+        (Value::Keyword(Keyword::If), false, false),
+        (
+            Value::Function(Vec::new(), Syntax::Block(Vec::new())),
+            false,
+            false,
+        ),
+        (Value::Class(Vec::new()), false, false),
+    ];
+    for (val, empty, default) in cases {
+        assert_eq!(val.is_empty(), empty, "{val:?}.is_empty()");
+        assert_eq!(val.is_default(), default, "{val:?}.is_default()");
+    }
+    assert!(Value::default().is_default());
+}
+
+#[test]
+fn equality_precision() {
+    let text = Value::from(" TRUE ");
+    let bool = Value::from(true);
+    assert_eq!(text.eq(&bool, 1), Value::from(true));
+    assert_eq!(text.eq(&bool, 2), Value::from(false));
+    assert_eq!(text.eq(&bool, 3), Value::from(false));
+
+    assert_eq_db!("equals(3, 3.1, 1)", "true");
+    assert_eq_db!("equals(3, 3.1, 2)", "false");
+    assert_eq_db!("equals(true, `true`, 2)", "true");
+    assert_eq_db!("equals(true, `true`, 3)", "false");
+    assert_eq_db!("equals(true, `true`)", "true === `true`");
+    assert_eq_db!("equals(true, `true`, 0)", "false");
+    assert_eq!(
+        eval("equals(3, 3, 2.7)"),
+        Err(String::from(
+            "`equals` requires a precision that's a whole number from 0 to 3; got `2.7`"
+        ))
+    );
+    assert!(eval("equals(3, 3, ;1)").is_err());
+    assert!(eval("equals(3, 3, 300)").is_err());
+    assert!(eval("equals(3, 3, `2`)").is_err());
+
+    assert_eq!(
+        eval_with(
+            |state| state.config.borrow_mut().default_precision = 3,
+            "equals(true, `true`)"
+        ),
+        Ok(Value::from(false))
+    );
+}
+
+#[test]
+fn hashcode() {
+    assert_eq_db!("hashcode(`hello`)", "hashcode(`hello`)");
+    assert_eq_db!("hashcode(`hello`) ==== hashcode(`world`)", "false");
+    assert_eq_db!(
+        "const var a = {}! a.x = 1! a.y = `two`! const var b = {}! b.y = `two`! b.x = 1! a ==== b",
+        "true"
+    );
+    assert_eq_db!(
+        "const var a = {}! a.x = 1! a.y = `two`! const var b = {}! b.y = `two`! b.x = 1! hashcode(a) ==== hashcode(b)",
+        "true"
+    );
+    assert_eq!(Value::from(1.0).hashcode(), Value::from(1.0).hashcode());
+}
+
+#[test]
+fn comprehension() {
+    assert_eq_db!("[1, 2, 3]", "[1,2,3]");
+    assert_eq_db!("[]", "[ ]");
+    assert_eq_db!("const const arr = [1, 2, 3]! arr.0", "2");
+    assert_eq_db!("const const arr = [1, 2, 3]! arr.5", "{}");
+    assert!(eval("const const arr = [1, 2, 3]! arr.(1e300)").is_err());
+    assert!(eval("const const arr = [1, 2, 3]! arr.(0.5)").is_err());
+    assert!(eval("const const arr = [1, 2, 3]! arr.nan").is_err());
+    assert!(eval("`abc`.(0.5)").is_err());
+    assert_eq_db!("[x + 1 for x in [1, 2, 3]]", "[2, 3, 4]");
+    assert_eq_db!(
+        "const const arr = [1, 2, 3, 4]! [x*x for x in arr if x > 2]",
+        "[9, 16]"
+    );
+    assert_eq_db!("[c + c for c in `ab`]", "[`aa`, `bb`]");
+    assert_eq_db!("const const x = 7! [x for x in [1]]! x", "7");
+    assert!(eval("[x for x in 5]").is_err());
+}
+
+#[test]
+fn range() {
+    assert_eq_db!("range(5)", "[0, 1, 2, 3, 4]");
+    assert_eq_db!("range(2, 5)", "[2, 3, 4]");
+    assert_eq_db!("range(5, 0, ;1)", "[5, 4, 3, 2, 1]");
+    assert_eq_db!("range(0, 1, 0.25)", "[0, 0.25, 0.5, 0.75]");
+    assert_eq_db!("range(5, 2)", "[]");
+    assert!(eval("range(0, 5, 0)").is_err());
+    assert!(eval("range(`a`)").is_err());
+    assert!(eval("range(inf)").is_err());
+    assert!(eval("range(0, 1, nan)").is_err());
+    assert!(eval("range(1e300)").is_err());
+    assert!(eval("range(0, 1, 0.00000001)").is_err());
+}
+
+#[test]
+fn zip_enumerate() {
+    assert_eq_db!("zip([1, 2], [`a`, `b`])", "[[1, `a`], [2, `b`]]");
+    assert_eq_db!("zip([1, 2, 3], [`a`])", "[[1, `a`]]");
+    assert_eq_db!("zip([], [1])", "[]");
+    assert_eq_db!(
+        "enumerate([`a`, `b`, `c`])",
+        "[[;1, `a`], [0, `b`], [1, `c`]]"
+    );
+    assert_eq_db!(
+        "const const arr = [`a`, `b`]! const const pair = enumerate(arr).0! const const first = ;1! const const idx = pair.first! arr.idx",
+        "`b`"
+    );
+    assert!(eval("zip(1, [1])").is_err());
+}
+
+#[test]
+fn count() {
+    assert_eq_db!("count(`banana`, `an`)", "2");
+    // matches don't overlap, so the middle `aa` of `aaaa` isn't counted
+    assert_eq_db!("count(`aaaa`, `aa`)", "2");
+    assert_eq_db!("count(`aabaa`, `aa`)", "2");
+    assert_eq_db!("count(`aaa`, `aa`)", "1");
+    assert_eq_db!("count(`aaaa`, ``)", "0");
+    assert_eq_db!("count([1, 2, 1, `1`], 1)", "3");
+    assert_eq_db!("count([1, 2, 1], 3)", "0");
+    let Value::Object(freq) = eval("count([`a`, `b`, `a`])").unwrap() else {
+        panic!("frequency map should be an object")
+    };
+    assert_eq!(freq.len(), 2);
+    assert_eq!(freq[&Value::from("a")], Value::from(2.0));
+    assert_eq!(freq[&Value::from("b")], Value::from(1.0));
+    assert!(eval("count(5)").is_err());
+    let Value::Object(freq) = eval("count([nan, nan])").unwrap() else {
+        panic!("frequency map should be an object")
+    };
+    assert_eq!(freq.len(), 1);
+    let Value::Object(freq) = eval("count([(x)->{x}, (y)->{y}])").unwrap() else {
+        panic!("frequency map should be an object")
+    };
+    assert_eq!(freq.len(), 2);
+}
+
+#[test]
+fn strict_lexing() {
+    let strict = Config {
+        strict_lexing: true,
+        ..Config::default()
+    };
+    assert_eq!(
+        crate::lexer::tokenize("a@#"),
+        Ok(vec![Token::Ident("a@#".into())])
+    );
+    assert_eq!(
+        crate::lexer::tokenize_with("a@#", &strict),
+        Err(String::from("Unexpected character `@` at 1:2"))
+    );
+    assert_eq!(
+        crate::lexer::tokenize_with("x!\n  @#", &strict),
+        Err(String::from("Unexpected character `@` at 2:3"))
+    );
+    assert_eq!(
+        crate::lexer::tokenize_with("café 👍 x_1", &strict),
+        crate::lexer::tokenize("café 👍 x_1")
+    );
+}
+
+#[test]
+fn keyword_tokens() {
+    assert_eq!(
+        crate::lexer::tokenize("if"),
+        Ok(vec![Token::Keyword(Keyword::If)])
+    );
+    assert_eq!(
+        crate::lexer::tokenize("iffy"),
+        Ok(vec![Token::Ident("iffy".into())])
+    );
+    assert_eq!(
+        crate::lexer::tokenize("consts var"),
+        Ok(vec![
+            Token::Ident("consts".into()),
+            Token::Space(1),
+            Token::Keyword(Keyword::Var)
+        ])
+    );
+    assert_eq!(
+        crate::lexer::tokenize("evaluate"),
+        Ok(vec![Token::Ident("evaluate".into())])
+    );
+    assert_eq_db!("const const iffy = 2! iffy", "2");
+    assert_eq_db!("const const if = 3! if", "3");
+    assert_eq_db!("if(true, 1, 2)", "1");
+}
+
+#[test]
+fn unicode_identifiers() {
+    let strict = Config {
+        strict_lexing: true,
+        ..Config::default()
+    };
+    for name in [
+        "café",
+        "cafe\u{301}",
+        "λ",
+        "αβγ",
+        "名前",
+        "_x1",
+        "x1",
+        "🥧",
+        "loop🤫",
+        "👩\u{200D}🚀",
+    ] {
+        assert_eq!(
+            crate::lexer::tokenize_with(name, &strict),
+            Ok(vec![Token::Ident(name.into())]),
+            "{name}"
+        );
+    }
+    assert_eq!(
+        crate::lexer::tokenize_with("123", &strict),
+        Ok(vec![Token::Ident("123".into())])
+    );
+    assert!(crate::lexer::tokenize_with("1x", &strict).is_err());
+    // combining marks can only go after something, and punctuation from other languages isn't part of names
+    assert!(crate::lexer::tokenize_with("\u{301}x", &strict).is_err());
+    assert!(crate::lexer::tokenize_with("「x", &strict).is_err());
+    assert!(crate::lexer::tokenize_with("x」", &strict).is_err());
+    assert_eq!(
+        crate::lexer::tokenize("x」"),
+        Ok(vec![Token::Ident("x".into()), Token::Ident("」".into())])
+    );
+    assert_eq!(
+        crate::lexer::tokenize("1x"),
+        Ok(vec![Token::Ident("1x".into())])
+    );
+}
+
+#[test]
+fn long_identifiers() {
+    let name = "a_😀é1".repeat(10_000);
+    let source = format!("{name}+{name} {name}");
+    assert_eq!(
+        crate::lexer::tokenize(&source),
+        Ok(vec![
+            Token::Ident(name.as_str().into()),
+            Token::Plus,
+            Token::Ident(name.as_str().into()),
+            Token::Space(1),
+            Token::Ident(name.as_str().into()),
+        ])
+    );
+    let strict = Config {
+        strict_lexing: true,
+        ..Config::default()
+    };
+    assert_eq!(
+        crate::lexer::tokenize_with(&name, &strict),
+        Ok(vec![Token::Ident(name.as_str().into())])
+    );
+}
+
+#[test]
+fn glob_matching() {
+    assert_eq_db!(r#"matches("hello.db", "*.db")"#, "true");
+    assert_eq_db!(r#"matches("hello.db", "*")"#, "true");
+    assert_eq_db!(r#"matches("", "*")"#, "true");
+    assert_eq_db!(r#"matches("abcbc", "a*bc")"#, "true");
+    assert_eq_db!(r#"matches("cat", "c?t")"#, "true");
+    assert_eq_db!(r#"matches("ct", "c?t")"#, "false");
+    assert_eq_db!(r#"matches("cat", "cat")"#, "true");
+    assert_eq_db!(r#"matches("cats", "cat")"#, "false");
+    assert_eq_db!(r#"matches("a cat", "cat")"#, "false");
+    assert_eq_db!(r#"matches("a*b", "a\*b")"#, "true");
+    assert_eq_db!(r#"matches("axb", "a\*b")"#, "false");
+    assert_eq_db!(r#"matches(123, "1*")"#, "true");
+}
+
+#[test]
+fn format_templates() {
+    assert_eq_db!(r#"format("{0} + {1} = {2}", [1, 2, 3])"#, r#""1 + 2 = 3""#);
+    assert_eq_db!(r#"format("{1}{0}{1}", ["a", "b"])"#, r#""bab""#);
+    assert_eq_db!(
+        r#"const var person = {}! person.name = "Ava"! format("hi {name}", person)"#,
+        r#""hi Ava""#
+    );
+    assert_eq_db!(
+        r#"const var person = {}! person.name = "Ava"! format("{name} is {0}", [7], person)"#,
+        r#""Ava is 7""#
+    );
+    assert_eq_db!(r#"format("{{0}} {0}", [1])"#, r#""{0} 1""#);
+    assert_eq_db!(r#"format("{0} {1} {name}", [1])"#, r#""1 {1} {name}""#);
+    assert!(eval(r#"format("{0} {1}", [1], true)"#).is_err());
+    assert!(eval(r#"format("{name}", {}, true)"#).is_err());
+    assert_eq_db!(r#"format("{0}", [1], true)"#, r#""1""#);
+}
+
+#[test]
+fn long_whitespace() {
+    let source = format!("1{}+ 2", " ".repeat(300));
+    assert_eq!(
+        crate::lexer::tokenize(&source),
+        Ok(vec![
+            Token::Ident("1".into()),
+            Token::Space(300),
+            Token::Plus,
+            Token::Space(1),
+            Token::Ident("2".into()),
+        ])
+    );
+    assert_eq!(
+        crate::lexer::tokenize(&"\n".repeat(100)),
+        Ok(vec![Token::Space(298)])
+    );
+    assert_eq_db!(&format!("1+2{}*3", " ".repeat(300)), "9");
+}
+
+#[test]
+fn deep_get() {
+    assert_eq_db!(
+        r#"const var player = {}! player.items = ["sword", "shield"]! deep_get(player, ["items", 0])"#,
+        r#""shield""#
+    );
+    assert_eq_db!(
+        r#"const var inner = {}! inner.name = "Rex"! const var player = {}! player.pets = [inner]! deep_get(player, ["pets", ;1, "name"])"#,
+        r#""Rex""#
+    );
+    assert_eq_db!(
+        r#"const var player = {}! player.items = ["sword"]! deep_get(player, ["items", 5, "name"])"#,
+        "{}"
+    );
+    assert_eq_db!(
+        r#"const var player = {}! deep_get(player, ["pets", 0, "name"])"#,
+        "{}"
+    );
+    assert_eq_db!("deep_get([[1, 2], [3, 4]], [0, ;1])", "3");
+    assert_eq_db!("deep_get([1, 2], [])", "[1, 2]");
+    assert!(eval("deep_get([1, 2], 0)").is_err());
+}
+
+#[test]
+fn assertions() {
+    assert_eq_db!(r#"assert(true, "fine")! 1"#, "1");
+    assert_eq_db!(r#"assert(2 ==== 2, "fine")! 1"#, "1");
+    assert_eq!(
+        eval(r#"assert(false, "this is wrong")"#),
+        Err(String::from("this is wrong"))
+    );
+    assert_eq!(
+        eval(r#"assert(maybe, "not sure")"#),
+        Err(String::from("not sure"))
+    );
+    assert_eq!(eval("assert(0)"), Err(String::from("Assertion failed")));
+}
+
+#[test]
+fn group_by() {
+    let Ok(Value::Object(groups)) = eval("group_by([1, 2, 3, 4, 5], x->{x % 2})") else {
+        panic!("`group_by` should make an object")
+    };
+    assert_eq!(
+        groups
+            .into_iter()
+            .map(|(key, group)| (key, group.clone_inner()))
+            .collect::<Vec<_>>(),
+        vec![
+            (Value::Number(0.0), eval("[2, 4]").unwrap()),
+            (Value::Number(1.0), eval("[1, 3, 5]").unwrap()),
+        ]
+    );
+    let Ok(Value::Object(groups)) =
+        eval(r#"group_by(["banana", "apple", "avocado", "blueberry", "cherry"], word->{word(0)})"#)
+    else {
+        panic!("`group_by` should make an object")
+    };
+    assert_eq!(
+        groups
+            .into_iter()
+            .map(|(key, group)| (key, group.clone_inner()))
+            .collect::<Vec<_>>(),
+        vec![
+            (Value::from("a"), eval(r#"["apple", "avocado"]"#).unwrap()),
+            (
+                Value::from("b"),
+                eval(r#"["banana", "blueberry"]"#).unwrap()
+            ),
+            (Value::from("c"), eval(r#"["cherry"]"#).unwrap()),
+        ]
+    );
+    assert_eq_db!("group_by([], x->{x})", "{}");
+    assert!(eval("group_by(1, x->{x})").is_err());
+    // keys that aren't usually compared still make groups
+    let Ok(Value::Object(groups)) = eval("group_by([nan, nan], x->{x})") else {
+        panic!("`group_by` should make an object")
+    };
+    assert_eq!(groups.len(), 1);
+    // functions with the same code are the same key
+    let Ok(Value::Object(groups)) = eval("group_by([1, 2, 1], (n) -> {(x) -> {n}})") else {
+        panic!("`group_by` should make an object")
+    };
+    assert_eq!(groups.len(), 1);
+}
+
+#[test]
+fn aggregates() {
+    assert_eq_db!("sum([1, 2, 3])", "6");
+    assert_eq_db!(r#"sum([1, "2", " 3 ", true])"#, "7");
+    assert_eq_db!("sum([])", "0");
+    assert_eq_db!(r#"product([2, "3", 4])"#, "24");
+    assert_eq_db!("product([])", "1");
+    assert_eq_db!(r#"average([1, "2", 3, 4])"#, "2.5");
+    assert_eq_db!("average([])", "maybe");
+    assert!(eval(r#"sum([1, "two"])"#).is_err());
+    assert!(eval("product([1, [2]])").is_err());
+    assert!(eval("average(3)").is_err());
+}
+
+#[test]
+fn pipes() {
+    assert_eq!(
+        crate::lexer::tokenize("x|>f||y"),
+        Ok(vec![
+            Token::Ident("x".into()),
+            Token::Pipe,
+            Token::Ident("f".into()),
+            Token::Or,
+            Token::Or,
+            Token::Ident("y".into()),
+        ])
+    );
+    assert_eq_db!(
+        "const const double = x->{x+x}! const const inc = x->{x+1}! 4 |> double |> inc",
+        "9"
+    );
+    assert_eq_db!(
+        "const const double = x->{x+x}! const const inc = x->{x+1}! 4 |> inc |> double",
+        "10"
+    );
+    assert_eq_db!(
+        r#"const const double = x->{x+x}! const const inc = x->{x+1}! const const show = x->{"got " + x}! 4 |> inc |> double |> show"#,
+        r#""got 10""#
+    );
+    assert_eq_db!("[1, 2, 3] |> sum", "6");
+}
+
+#[test]
+fn to_object() {
+    assert_eq_db!(
+        r#"const var player = to_object([["name", "Ava"], ["health", 10]])! player.health"#,
+        "10"
+    );
+    assert_eq_db!(
+        r#"const var player = to_object([["name", "Ava"], ["health", 10]])! player.name"#,
+        r#""Ava""#
+    );
+    assert_eq_db!(
+        r#"const var player = to_object([["health", 10], ["health", 3]])! player.health"#,
+        "3"
+    );
+    assert_eq_db!(
+        r#"const var player = to_object([["health", 10]])! player.health += 1! player.health"#,
+        "11"
+    );
+    assert_eq_db!("to_object([])", "{}");
+    assert!(eval(r#"to_object([["name"]])"#).is_err());
+    assert!(eval(r#"to_object([["name", "Ava", 3]])"#).is_err());
+    assert!(eval(r#"to_object(["name"])"#).is_err());
+}
+
+#[test]
+fn diff() {
+    let setup = r#"const var old = to_object([["name", "Ava"], ["age", 1], ["pet", "cat"]])!
+const var new = to_object([["name", "Ava"], ["age", 2], ["job", "dev"]])!
+const var changes = diff(old, new)!"#;
+    assert_eq_db!(format!("{setup} changes.added.job"), r#""dev""#);
+    assert_eq_db!(format!("{setup} changes.removed.pet"), r#""cat""#);
+    assert_eq_db!(format!("{setup} changes.changed.age"), "[1, 2]");
+    assert_eq_db!(format!("{setup} changes.changed.name"), "{}");
+    assert_eq_db!(
+        r#"const var old = to_object([["pos", to_object([["x", 1], ["y", 2]])]])!
+const var new = to_object([["pos", to_object([["x", 1], ["y", 3]])]])!
+const var changes = diff(old, new)!
+changes.changed.pos.changed.y"#,
+        "[2, 3]"
+    );
+    assert_eq_db!(
+        r#"const var obj = to_object([["name", "Ava"], ["age", 1]])! diff(obj, to_object([["age", "1"], ["name", "Ava"]]))"#,
+        "{}"
+    );
+    assert_eq_db!(
+        r#"diff(to_object([["on", 0]]), to_object([["on", false]]))"#,
+        "{}"
+    );
+    assert_eq_db!(
+        r#"const var changes = diff(to_object([["on", 0]]), to_object([["on", false]]), 3)! changes.changed.on"#,
+        "[0, false]"
+    );
+    assert!(eval("diff([1], [2])").is_err());
+    assert_eq!(
+        eval("diff({}, {}, 1.5)"),
+        Err(String::from(
+            "`diff` requires a precision that's a whole number from 0 to 3; got `1.5`"
+        ))
+    );
+    assert!(eval("diff({}, {}, 4)").is_err());
+}
+
+#[test]
+fn freezing() {
+    let setup = "const var settings = {}! settings.volume = 11! freeze(settings)!";
+    assert_eq_db!(
+        format!("{setup} settings.volume = 3! settings.volume"),
+        "11"
+    );
+    assert_eq_db!(
+        format!("{setup} settings.volume += 3! settings.volume"),
+        "11"
+    );
+    assert_eq_db!(format!("{setup} settings.volume++! settings.volume"), "11");
+    assert_eq_db!(
+        format!("{setup} settings.theme = 1! settings.theme"),
+        "undefined"
+    );
+    assert_eq_db!(
+        format!("{setup} const var alias = settings! alias.volume = 3! settings.volume"),
+        "11"
+    );
+    assert_eq_db!(
+        "const var outer = {}! outer.inner = {}! outer.inner.x = 1! freeze(outer)! outer.inner.x = 2! outer.inner.x",
+        "1"
+    );
+    assert_eq_db!(
+        "const var other = {}! other.volume = 11! other.volume = 3! other.volume",
+        "3"
+    );
+
+    let run = |src: &str| {
+        eval_with(
+            |state| state.config.borrow_mut().strict_freezing = true,
+            format!("{setup} {src}"),
+        )
+    };
+    assert_eq!(run("settings.volume"), Ok(Value::from(11.0)));
+    assert!(run("settings.volume = 3!").is_err());
+    assert!(run("settings.theme = 1!").is_err());
+}
+
+#[test]
+fn clamp() {
+    assert_eq_db!("clamp(;5, 0, 10)", "0");
+    assert_eq_db!("clamp(5, 0, 10)", "5");
+    assert_eq_db!("clamp(15, 0, 10)", "10");
+    assert_eq_db!("clamp(10, 0, 10)", "10");
+    assert_eq_db!("clamp(15, 10, 0)", "10");
+    assert_eq_db!("clamp(;5, 10, 0)", "0");
+    assert_eq_db!("clamp(5, 10, 0)", "5");
+    assert_eq_db!(r#"clamp("hi", 0, 10)"#, r#""hi""#);
+    assert_eq!(
+        Value::clamp(
+            &Value::Number(f64::NAN),
+            &Value::from(0.0),
+            &Value::from(1.0)
+        )
+        .map(|val| val.to_number().map(f64::is_nan)),
+        Ok(Some(true))
+    );
+    assert!(eval(r#"clamp(5, "low", 10)"#).is_err());
+}
+
+#[test]
+fn comments() {
+    let source = "a // line comment\n/* block\ncomment */ b/c /= 2";
+    assert_eq!(
+        crate::lexer::tokenize(source),
+        Ok(vec![
+            Token::Ident("a".into()),
+            Token::Space(1),
+            Token::Space(1),
+            Token::Space(1),
+            Token::Ident("b".into()),
+            Token::Slash,
+            Token::Ident("c".into()),
+            Token::Space(1),
+            Token::SlashEq,
+            Token::Space(1),
+            Token::Ident("2".into()),
+        ])
+    );
+    let preserve = Config {
+        preserve_comments: true,
+        ..Config::default()
+    };
+    let tokens = crate::lexer::tokenize_with(source, &preserve).unwrap();
+    assert_eq!(
+        tokens[..6],
+        [
+            Token::Ident("a".into()),
+            Token::Space(1),
+            Token::Comment("// line comment".into()),
+            Token::Space(1),
+            Token::Comment("/* block\ncomment */".into()),
+            Token::Space(1),
+        ]
+    );
+    assert!(crate::parser::parse(tokens).is_ok());
+    assert!(crate::lexer::tokenize("a /* never ends").is_err());
+    assert_eq_db!("1 + 2 // + 3\n", "3");
+    assert_eq_db!("1 /* + 2 */ + 3", "4");
+}
+
+#[test]
+fn object_literals() {
+    assert_eq_db!(
+        r#"const var obj = {name: "Ava", age: 1}! obj.name"#,
+        r#""Ava""#
+    );
+    assert_eq_db!(r#"const var obj = {name: "Ava", age: 1}! obj.age"#, "1");
+    assert_eq_db!(
+        r#"const var obj = {"with space": 1, 2: "two", if: 3}! obj.2"#,
+        r#""two""#
+    );
+    assert_eq_db!(
+        r#"deep_get({"with space": 1, 2: "two"}, ["with space"])"#,
+        "1"
+    );
+    assert_eq_db!(r#"{2: "two"}.2"#, r#""two""#);
+    assert_eq_db!(r#"deep_get({2: "two"}, [2])"#, "undefined");
+    assert_eq_db!(r#"deep_get({2: "two"}, ["2"])"#, r#""two""#);
+    assert_eq_db!("const var obj = {pos: {x: 1, y: 2 + 3}}! obj.pos.y", "5");
+    assert_eq_db!(
+        "const var obj = {pos: {x: 1}}! obj.pos.x = 4! obj.pos.x",
+        "4"
+    );
+    assert_eq_db!(
+        r#"{name: "Ava", age: 1}"#,
+        r#"to_object([["age", 1], ["name", "Ava"]])"#
+    );
+    assert_eq_db!("const const x = 1! {x}", "1");
+    assert!(eval("{a: 1, 2}").is_err());
+}
+
+#[test]
+fn spreading() {
+    assert_eq_db!(
+        "const var a = [1, 2]! const var b = [3]! [...a, ...b, 4]",
+        "[1, 2, 3, 4]"
+    );
+    assert_eq_db!(r#"[0, ..."hi"]"#, r#"[0, "h", "i"]"#);
+    assert_eq_db!("[...[]]", "[]");
+    let base = "const var base = {name: \"Ava\", age: 1}!";
+    assert_eq_db!(
+        format!("{base} const var obj = {{...base, pet: \"cat\"}}! obj.name"),
+        r#""Ava""#
+    );
+    assert_eq_db!(
+        format!("{base} const var obj = {{...base, pet: \"cat\"}}! obj.pet"),
+        r#""cat""#
+    );
+    assert_eq_db!(
+        format!("{base} const var obj = {{...base, age: 2}}! obj.age"),
+        "2"
+    );
+    assert_eq_db!(
+        format!("{base} const var obj = {{age: 2, ...base}}! obj.age"),
+        "1"
+    );
+    assert_eq_db!(
+        format!("{base} const var obj = {{...base}}! obj.age = 5! base.age"),
+        "1"
+    );
+    assert_eq_db!(
+        "const var a = [1]! const var b = [...a]! b.;1 = 5! a.;1",
+        "1"
+    );
+    assert!(eval("const var a = [1]! {...a}").is_err());
+    assert!(eval("...[1]").is_err());
+    assert!(crate::lexer::tokenize("a..b").is_err());
+}
+
+#[test]
+fn same_identity() {
+    let setup = r#"const var original = {name: "Ava"}! const var alias = original! const var copy = {...original}!"#;
+    assert_eq_db!(format!("{setup} same_identity(original, alias)"), "true");
+    assert_eq_db!(format!("{setup} same_identity(original, copy)"), "false");
+    assert_eq_db!(format!("{setup} original === copy"), "true");
+    assert_eq_db!(format!("{setup} original ===== alias"), "true");
+    assert_eq_db!(format!("{setup} original ===== copy"), "false");
+    assert_eq_db!("const const pi = 3.14! same_identity(pi, pi)", "true");
+    assert_eq_db!("same_identity([1], [1])", "false");
+}
+
+#[test]
+fn unterminated_strings() {
+    for source in [
+        "\"abc",
+        "'abc",
+        "`abc",
+        "«abc",
+        "»abc",
+        "„abc",
+        "x = \"abc\\\"",
+    ] {
+        let Err(err) = crate::lexer::tokenize(source) else {
+            panic!("`{source}` should be an unterminated string");
+        };
+        assert!(err.starts_with("Unterminated string literal"), "{err}");
+    }
+    assert_eq!(
+        crate::lexer::tokenize("x\n  \"abc"),
+        Err(String::from("Unterminated string literal at 2:6"))
+    );
+    assert!(crate::lexer::tokenize("\"abc\" 'abc' `abc` «abc» »abc« „abc“").is_ok());
+}
+
+#[test]
+fn unterminated_interpolation() {
+    for source in [
+        "\"a${b",
+        "\"a${b\"",
+        "\"a{b",
+        "\"a{b\"",
+        "'a£{b'",
+        "„a{b$c“",
+    ] {
+        let Err(err) = crate::lexer::tokenize(source) else {
+            panic!("`{source}` should have an unterminated interpolation");
+        };
+        assert!(
+            err.starts_with("Unterminated string interpolation"),
+            "{err}"
+        );
+    }
+    assert!(crate::lexer::tokenize("\"a${b}\" \"a{b}\" \"{b}€\" \"{e$b}\"").is_ok());
+}
+
+#[test]
+fn token_limit() {
+    let limited = Config {
+        max_tokens: Some(5),
+        ..Config::default()
+    };
+    // `a`, ` `, `+`, ` `, `b`
+    assert!(crate::lexer::tokenize_with("a + b", &limited).is_ok());
+    assert_eq!(
+        crate::lexer::tokenize_with("a + b!", &limited),
+        Err(String::from("Program has more than 5 tokens at 1:6"))
+    );
+    assert!(crate::lexer::tokenize_with(&"a ".repeat(10_000), &limited).is_err());
+    assert!(crate::lexer::tokenize("a + b!").is_ok());
+    // comments that are thrown away don't count
+    assert!(crate::lexer::tokenize_with("a // lots of words\n", &limited).is_ok());
+    // the `!`s that newlines add do count
+    let with_newlines = Config {
+        max_tokens: Some(3),
+        newline_statements: true,
+        ..Config::default()
+    };
+    assert!(crate::lexer::tokenize_with("a\nb", &with_newlines).is_err());
+    assert!(crate::lexer::tokenize_with(
+        "a\nb",
+        &Config {
+            max_tokens: Some(4),
+            ..with_newlines
+        }
+    )
+    .is_ok());
+}
+
+#[test]
+fn string_length_limit() {
+    let limited = Config {
+        max_string_length: Some(5),
+        ..Config::default()
+    };
+    assert!(crate::lexer::tokenize_with("\"hello\"", &limited).is_ok());
+    assert!(crate::lexer::tokenize_with("\"héllo\"", &limited).is_ok());
+    assert!(crate::lexer::tokenize_with("\"hello!\"", &limited).is_err());
+    assert!(crate::lexer::tokenize("\"hello!\"").is_ok());
+
+    let run = |src: &str| {
+        eval_with(
+            |state| state.config.borrow_mut().max_string_length = Some(5),
+            src,
+        )
+    };
+    assert_eq!(run(r#""ab" * 2"#), Ok(Value::from("abab")));
+    assert!(run(r#""ab" * 3"#).is_err());
+    assert!(run(r#""ab" * 1000000000000000000"#).is_err());
+    assert!(run(r#""ab" * inf"#).is_err());
+    assert!(run(r#"const var s = "ab"! s *= 3!"#).is_err());
+    assert_eq_db!(r#""ab" * 3"#, r#""ababab""#);
+    // without a limit, strings still can't be multiplied forever
+    assert!(eval(r#""ab" * inf"#).is_err());
+    assert!(eval(r#""ab" * 1000000000000000000"#).is_err());
+    assert!(Value::arith(
+        Operation::Mul,
+        Value::from("ab"),
+        Value::from(f64::INFINITY)
+    )
+    .is_err());
+    // the lexer gives up as soon as it's read too much of a literal
+    assert!(crate::lexer::tokenize_with("\"hello!", &limited)
+        .unwrap_err()
+        .contains("limit"));
+}
+
+#[test]
+fn char_at() {
+    assert_eq_db!(r#"char_at("hello", 0)"#, r#""h""#);
+    assert_eq_db!(r#"char_at("hello", 4)"#, r#""o""#);
+    assert_eq_db!(r#"char_at("hello", 5)"#, "undefined");
+    assert_eq_db!(r#"char_at("hello", ;1)"#, r#""o""#);
+    assert_eq_db!(r#"char_at("hello", ;5)"#, r#""h""#);
+    assert_eq_db!(r#"char_at("hello", ;6)"#, "undefined");
+    assert_eq_db!(r#"char_at("héllo👍", 1)"#, r#""é""#);
+    assert_eq_db!(r#"char_at("héllo👍", ;1)"#, r#""👍""#);
+    assert!(eval("char_at(5, 0)").is_err());
+}
+
+#[test]
+fn code_points() {
+    assert_eq_db!(r#"ord("A")"#, "65");
+    assert_eq_db!(r#"ord("abc")"#, "97");
+    assert_eq_db!(r#"ord("👍")"#, "128077");
+    assert_eq_db!("chr(65)", r#""A""#);
+    assert_eq_db!("chr(128077)", r#""👍""#);
+    assert_eq_db!(r#"chr(ord("é"))"#, r#""é""#);
+    assert!(eval("chr(55296)").is_err());
+    assert!(eval("chr(1114112)").is_err());
+    assert!(eval("chr(;1)").is_err());
+    assert!(eval("chr(6.5)").is_err());
+    assert!(eval(r#"ord("")"#).is_err());
+    assert!(eval("ord(5)").is_err());
+}
+
+#[test]
+fn random_numbers() {
+    let mut rng = Rng::seeded(7);
+    let first = (0..5).map(|_| rng.next_f64()).collect::<Vec<_>>();
+    let mut rng = Rng::seeded(7);
+    assert_eq!(first, (0..5).map(|_| rng.next_f64()).collect::<Vec<_>>());
+    assert!(first.iter().all(|num| (0.0..1.0).contains(num)));
+    let mut other = Rng::seeded(8);
+    assert_ne!(first, (0..5).map(|_| other.next_f64()).collect::<Vec<_>>());
+
+    let source =
+        "seed(42)! [random(), random(), random_int(1, 6), random_int(1, 6), random_int(;3, 3)]";
+    assert_eq!(eval(source), eval(source));
+    assert_ne!(
+        eval(source),
+        eval(
+            "seed(43)! [random(), random(), random_int(1, 6), random_int(1, 6), random_int(;3, 3)]"
+        )
+    );
+    for _ in 0..50 {
+        let Ok(Value::Number(roll)) = eval("random_int(1, 6)") else {
+            panic!("`random_int` should give a number")
+        };
+        assert!((1.0..=6.0).contains(&roll) && roll.fract() == 0.0, "{roll}");
+    }
+    assert_eq_db!("random_int(4, 4)", "4");
+    assert!(eval("random_int(5, 4)").is_err());
+}
+
+#[test]
+fn collapse() {
+    assert_eq_db!("collapse(maybe, 0)", "false");
+    assert_eq_db!("collapse(maybe, 1)", "true");
+    assert_eq_db!("collapse(true, 0)", "true");
+    assert_eq_db!("collapse(false, 1)", "false");
+    assert_eq_db!(r#"collapse("hello")"#, "true");
+    assert_eq_db!("collapse(0)", "false");
+
+    let source = "seed(1)! [collapse(maybe), collapse(maybe), collapse(maybe, 0.5), collapse(0.5), collapse(0.5)]";
+    assert_eq_db!(source, "[false, false, true, true, false]");
+    let mut rng = Rng::seeded(3);
+    let flips = (0..1000)
+        .filter(|_| Value::Boolean(Boolean::Maybe).collapse(None, &mut rng) == Value::from(true))
+        .count();
+    assert!((400..600).contains(&flips), "{flips}");
+    let mut rng = Rng::seeded(3);
+    let flips = (0..1000)
+        .filter(|_| Value::Number(0.1).collapse(None, &mut rng) == Value::from(true))
+        .count();
+    assert!((50..150).contains(&flips), "{flips}");
+    // an explicit probability wins over the value's own
+    let mut rng = Rng::seeded(3);
+    let flips = (0..1000)
+        .filter(|_| Value::Number(0.1).collapse(Some(0.5), &mut rng) == Value::from(true))
+        .count();
+    assert!((400..600).contains(&flips), "{flips}");
+    let mut rng = Rng::seeded(3);
+    let flips = (0..1000)
+        .filter(|_| {
+            Value::Boolean(Boolean::Maybe).collapse(Some(0.25), &mut rng) == Value::from(true)
+        })
+        .count();
+    assert!((200..300).contains(&flips), "{flips}");
+}
+
+#[test]
+fn sign() {
+    assert_eq_db!("sign(42)", "1");
+    assert_eq_db!("sign(;7)", ";1");
+    assert_eq_db!("sign(0)", "0");
+    assert_eq_db!(r#"sign("7")"#, "1");
+    assert_eq_db!("sign(true)", "1");
+    assert_eq!(Value::Number(-0.0).sign(), Value::Number(0.0));
+    assert!(!Value::Number(-0.0)
+        .sign()
+        .to_number()
+        .unwrap()
+        .is_sign_negative());
+    assert!(Value::Number(f64::NAN).sign().to_number().unwrap().is_nan());
+    assert!(Value::from(Vec::<Value>::new())
+        .sign()
+        .to_number()
+        .unwrap()
+        .is_nan());
+}
+
+#[test]
+fn math() {
+    assert_eq_db!("sqrt(16)", "4");
+    assert_eq_db!("cos(0)", "1");
+    assert_eq_db!("sin(0)", "0");
+    assert_eq_db!("tan(0)", "0");
+    assert_eq_db!("exp(0)", "1");
+    assert_eq_db!("ln(1)", "0");
+    assert_eq_db!("log(1000)", "3");
+    assert_eq_db!(r#"sqrt("nope")"#, "maybe");
+    assert_eq_db!("sin(🥧 / 2)", "1");
+    assert_eq_db!("ln(exp(2))", "2");
+    assert!(eval("sqrt(;1)").unwrap().to_number().unwrap().is_nan());
+    assert_eq!(eval("ln(0)"), Ok(Value::Number(f64::NEG_INFINITY)));
+}
+
+#[test]
+fn custom_builtins() {
+    fn shout(args: &[Value]) -> SResult<Value> {
+        let [Value::String(str)] = args else {
+            return Err(String::from("`shout` requires a string"));
+        };
+        Ok(Value::from(format!("{}!", str.to_uppercase()).as_str()))
+    }
+    fn add_all(args: &[Value]) -> SResult<Value> {
+        args.iter()
+            .map(|arg| {
+                arg.to_number()
+                    .ok_or_else(|| format!("`{arg}` isn't a number"))
+            })
+            .sum::<SResult<f64>>()
+            .map(Value::Number)
+    }
+
+    let run = |src: &str| {
+        eval_with(
+            |state| {
+                let mut builtins = state.builtins.borrow_mut();
+                builtins.register("shout", shout);
+                builtins.register("add_all", add_all);
+            },
+            src,
+        )
+    };
+    assert_eq!(run(r#"shout("hi")"#), Ok(Value::from("HI!")));
+    assert_eq!(run("add_all(1, 2, 3)"), Ok(Value::Number(6.0)));
+    assert_eq!(run(r#""hey" |> shout"#), Ok(Value::from("HEY!")));
+    assert_eq!(
+        run("shout(1)"),
+        Err(String::from("`shout` requires a string"))
+    );
+    // variables and keywords still win
+    assert_eq!(run("const const shout = 5! shout"), Ok(Value::Number(5.0)));
+    // scripts without the builtin registered just see a string
+    assert_eq_db!("add_all", r#""add_all""#);
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn json() {
+    let json = serde_json::json!({
+        "name": "DreamBerd",
+        "tags": ["perfect", 3, null],
+        "nested": { "ready": true, "done": false }
+    });
+    let value = Value::from(json.clone());
+    assert_eq!(
+        value,
+        eval(r#"{name: "DreamBerd", tags: ["perfect", 3, maybe], nested: {ready: true, done: false}}"#)
+            .unwrap()
+    );
+    assert_eq!(serde_json::Value::try_from(value), Ok(json));
+
+    assert_eq!(
+        Value::from(serde_json::Value::Null),
+        Value::Boolean(Boolean::Maybe)
+    );
+    assert_eq!(
+        serde_json::Value::try_from(Value::Boolean(Boolean::Maybe)),
+        Ok(serde_json::Value::Null)
+    );
+    assert_eq!(
+        serde_json::Value::try_from(eval("[1, 2.5]").unwrap()),
+        Ok(serde_json::json!([1, 2.5]))
+    );
+    assert!(serde_json::Value::try_from(Value::Number(f64::NAN)).is_err());
+    assert!(serde_json::Value::try_from(eval("(x) -> {x}").unwrap()).is_err());
+}
+
+#[test]
+fn iterate_from_rust() {
+    let obj = eval(r#"{b: 2, a: "one"}"#).unwrap();
+    assert_eq!(
+        obj.into_iter().collect::<Vec<_>>(),
+        vec![
+            (Value::from("a"), Value::from("one")),
+            (Value::from("b"), Value::Number(2.0)),
+        ]
+    );
+
+    let arr = eval(r#"[1, "two", true]"#).unwrap();
+    assert_eq!(
+        arr.into_iter().map(|(_, item)| item).collect::<Vec<_>>(),
+        vec![Value::Number(1.0), Value::from("two"), Value::from(true)]
+    );
+    assert_eq!(
+        (&arr).into_iter().map(|(idx, _)| idx).collect::<Vec<_>>(),
+        vec![Value::Number(0.0), Value::Number(1.0), Value::Number(2.0)]
+    );
+
+    let mut chars = String::new();
+    for (_, ch) in &Value::from("héllo") {
+        chars.push_str(&ch.to_string());
+        chars.push(',');
+    }
+    assert_eq!(chars, "h,é,l,l,o,");
+
+    assert_eq!(Value::Number(5.0).into_iter().count(), 0);
+}
+
+#[test]
+fn accessors() {
+    let arr = eval("[1, 2]").unwrap();
+    let obj = eval("{a: 1}").unwrap();
+    assert_eq!(arr.as_array().map(<[Pointer]>::len), Some(2));
+    assert_eq!(obj.as_array(), None);
+}
+
+#[test]
+fn walk() {
+    let value =
+        eval(r#"{name: "Jo", tags: ["a", "b"], inner: {secret: "shh", count: 2}}"#).unwrap();
+    let mut nodes = 0;
+    value.walk(|_, _| nodes += 1);
+    assert_eq!(nodes, 8);
+
+    let mut paths = Vec::new();
+    value.walk(|path, val| {
+        if matches!(val, Value::String(_)) {
+            paths.push(
+                path.iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("."),
+            );
+        }
+    });
+    assert_eq!(paths, vec!["inner.secret", "name", "tags.0", "tags.1"]);
+
+    let mut redacted = value;
+    redacted.walk_mut(|_, val| {
+        if matches!(val, Value::String(_)) {
+            *val = Value::from("***");
+        }
+    });
+    assert_eq!(
+        redacted,
+        eval(r#"{name: "***", tags: ["***", "***"], inner: {secret: "***", count: 2}}"#).unwrap()
+    );
+
+    // `const` and frozen values stay the way they are
+    let mut guarded =
+        eval(r#"const var obj = {a: "x"}! obj.inner = {b: "y"}! freeze(obj.inner)! obj"#).unwrap();
+    let Value::Object(obj) = &mut guarded else {
+        panic!("{guarded}");
+    };
+    obj.insert(Value::from("c"), Pointer::from(Value::from("z")));
+    guarded.walk_mut(|_, val| {
+        if matches!(val, Value::String(_)) {
+            *val = Value::from("***");
+        }
+    });
+    assert_eq!(
+        guarded,
+        eval(r#"{a: "***", inner: {b: "y"}, c: "z"}"#).unwrap()
+    );
+
+    // objects that contain themselves don't loop forever
+    let looped = eval("const var obj = {a: 1}! obj.me = obj! obj").unwrap();
+    let mut nodes = 0;
+    looped.walk(|_, _| nodes += 1);
+    assert!(nodes < 10, "{nodes}");
+    let mut looped = looped;
+    looped.walk_mut(|_, val| {
+        if let Value::Number(num) = val {
+            *num += 1.0;
+        }
+    });
+}
+
+#[test]
+fn non_finite_literals() {
+    assert_eq!(eval("inf"), Ok(Value::Number(f64::INFINITY)));
+    assert_eq!(eval("-inf"), Ok(Value::Number(f64::NEG_INFINITY)));
+    assert!(matches!(eval("nan"), Ok(Value::Number(num)) if num.is_nan()));
+    assert_eq!(eval("inf + 1"), Ok(Value::Number(f64::INFINITY)));
+    assert_eq!(eval("-inf * 2"), Ok(Value::Number(f64::NEG_INFINITY)));
+    assert_eq_db!("1 / inf", "0");
+    assert_eq_db!("-inf < ;1000000", "true");
+    assert!(matches!(eval("nan + 1"), Ok(Value::Number(num)) if num.is_nan()));
+    assert!(matches!(eval("inf - inf"), Ok(Value::Number(num)) if num.is_nan()));
+    assert!(eval("-nan").is_err());
+
+    assert_eq_db!("const const inf = 5! inf + 1", "6");
+    assert_eq_db!("const const inf = 5! -inf", ";5");
+    assert_eq_db!("const const nan = 2! nan * 2", "4");
+}
+
+#[test]
+fn pretty_display() {
+    let value =
+        eval(r#"{name: "Jo", tags: ["a", {x: 1}], inner: {count: 2}, empty: [], none: undefined}"#)
+            .unwrap();
+    assert_eq!(
+        format!("{value}"),
+        r#"object { empty: var var ([]), inner: var var (object { count: var var (2) }), name: var var ("Jo"), none: var var (undefined), tags: var var (["a", object { x: var var (1) }]) }"#
+    );
+    assert_eq!(
+        format!("{value:#}"),
+        r#"object {
+    empty: [],
+    inner: object {
+        count: 2,
+    },
+    name: "Jo",
+    none: undefined,
+    tags: [
+        "a",
+        object {
+            x: 1,
+        },
+    ],
+}"#
+    );
+    assert_eq!(format!("{:#}", Value::from("hi")), "hi");
+    assert_eq!(format!("{:#}", Value::Number(3.0)), "3");
+}
+
+#[test]
+fn depth_and_size() {
+    let flat = eval(r#"{a: 1, b: "two"}"#).unwrap();
+    assert_eq!(flat.depth(), 1);
+    assert_eq!(flat.size(), 3);
+
+    let nested = eval("{a: {b: {c: [1, [2]]}}}").unwrap();
+    assert_eq!(nested.depth(), 5);
+    assert_eq!(nested.size(), 7);
+
+    assert_eq!(Value::Number(1.0).depth(), 0);
+    assert_eq!(Value::Number(1.0).size(), 1);
+    assert_eq!(eval("[]").unwrap().depth(), 1);
+
+    // shared objects are counted once per reference
+    let shared = eval("const var inner = {x: 1}! [inner, inner]").unwrap();
+    assert_eq!(shared.size(), 5);
+    assert_eq!(shared.depth(), 2);
+
+    // objects that contain themselves still finish
+    let looped = eval("const var obj = {a: 1}! obj.me = obj! obj").unwrap();
+    assert!(looped.size() < 10);
+    assert!(looped.depth() < 10);
+}
+
+#[test]
+fn cyclic_values() {
+    let looped = eval("const var obj = {a: 1}! obj.me = obj! obj").unwrap();
+    assert_eq!(
+        format!("{looped}"),
+        "object { a: var var (1), me: var var (object { a: var var (1), me: var var (<cycle>) }) }"
+    );
+    assert!(format!("{looped:#}").contains("<cycle>"));
+    let hash = |val: &Value| {
+        let mut hasher = std::hash::DefaultHasher::new();
+        std::hash::Hash::hash(val, &mut hasher);
+        std::hash::Hasher::finish(&hasher)
+    };
+    assert_eq!(hash(&looped), hash(&looped));
+
+    let looped_arr =
+        eval("const var obj = {a: 1}! const var arr = [obj]! obj.arr = arr! arr").unwrap();
+    assert!(format!("{looped_arr}").contains("<cycle>"));
+
+    assert_eq_db!(
+        "const var a = {x: 1}! a.me = a! const var b = {x: 1}! b.me = b! a == b",
+        "true"
+    );
+    assert_eq_db!(
+        "const var a = {x: 1}! a.me = a! const var b = {x: 2}! b.me = b! a == b",
+        "false"
+    );
+    assert_eq_db!(
+        "const var a = {x: 1}! a.me = a! hashcode(a) == hashcode(a)",
+        "true"
+    );
+}
+
+#[test]
+fn strict_conditions() {
+    assert_eq!(Value::from(true).coerce_bool_strict(), Ok(true));
+    assert_eq!(Value::Number(0.0).coerce_bool_strict(), Ok(false));
+    assert_eq!(Value::from("hi").coerce_bool_strict(), Ok(true));
+    assert!(Value::Boolean(Boolean::Maybe).coerce_bool_strict().is_err());
+    assert!(Value::Number(0.5).coerce_bool_strict().is_err());
+
+    let run = |src: &str| {
+        eval_with(
+            |state| state.config.borrow_mut().strict_conditions = true,
+            src,
+        )
+    };
+    assert_eq!(run("if(true, 1, 2, 3)"), Ok(Value::Number(1.0)));
+    assert_eq!(run("if(false, 1, 2, 3)"), Ok(Value::Number(2.0)));
+    assert!(run("if(maybe, 1, 2, 3)").is_err());
+    assert!(run("assert(true)").is_ok());
+    assert_eq!(run("assert(false)"), Err(String::from("Assertion failed")));
+    assert!(run("assert(maybe)").unwrap_err().contains("maybeyey"));
+    assert!(run("[x for x in [1, 0.5, 0] if x]").is_err());
+
+    // without strict conditions, `maybe` picks the fourth branch
+    assert_eq_db!("if(maybe, 1, 2, 3)", "3");
+}
+
+#[test]
+fn loops() {
+    assert_eq_db!("var var i = 0! while(i < 5, { i += 1! })! i", "5");
+    assert_eq_db!("var var i = 0! while(false, { i += 1! })! i", "0");
+    assert_eq_db!("var var i = 0! do_while({ i += 1! }, false)! i", "1");
+    assert_eq_db!("var var i = 10! do_while({ i += 1! }, i < 5)! i", "11");
+    assert_eq_db!("var var i = 0! do_while({ i += 1! }, i < 5)! i", "5");
+    // `maybe` stops a loop
+    assert_eq_db!("var var i = 0! do_while({ i += 1! }, maybe)! i", "1");
+    assert_eq_db!("var var i = 0! while(maybe, { i += 1! })! i", "0");
+    // loops give back the last value of their body
+    assert_eq_db!("var var i = 0! while(i < 3, { i += 1! i * 10 })", "30");
+}
+
+#[test]
+fn break_and_continue() {
+    assert_eq_db!(
+        "var var i = 0! while(true, { i += 1! if(i >= 3, break())! })! i",
+        "3"
+    );
+    assert_eq_db!(
+        "var var i = 0! var var odd = 0! while(i < 6, { i += 1! if(i % 2 ==== 0, continue())! odd += 1! })! odd",
+        "3"
+    );
+    assert_eq_db!(
+        "var var i = 0! do_while({ i += 1! continue()! i = 100! }, i < 3)! i",
+        "3"
+    );
+
+    // breaking an inner loop keeps the outer one going
+    assert_eq_db!(
+        "var var total = 0! var var i = 0! while(i < 3, { i += 1! var var j = 0! while(true, { j += 1! if(j > 2, break())! total += 1! })! })! total",
+        "6"
+    );
+    // continuing an outer loop skips the rest of its body
+    assert_eq_db!(
+        "var var total = 0! var var i = 0! while(i < 3, { i += 1! var var j = 0! while(true, { j += 1! if(j > 1, continue(\"outer\"))! total += 1! })! total += 100! }, \"outer\")! total",
+        "3"
+    );
+    // a labeled break escapes two levels at once
+    assert_eq_db!(
+        "var var count = 0! while(true, { while(true, { while(true, { count += 1! break(\"outer\")! })! count += 10! })! count += 100! }, \"outer\")! count",
+        "1"
+    );
+
+    assert_eq!(
+        eval("break()"),
+        Err(String::from("Can't `break` outside of a loop"))
+    );
+    assert_eq!(
+        eval("while(true, break(\"nope\"))"),
+        Err(String::from("There's no loop labeled `nope` to `break`"))
+    );
+    // functions don't let signals out
+    assert_eq!(
+        eval("const const stop = () -> { break()! }! while(true, stop())"),
+        Err(String::from("Can't `break` outside of a loop"))
+    );
+}
+
+#[test]
+fn interpolate() {
+    assert_eq_db!(r#"interpolate("just text", {a: 1})"#, r#""just text""#);
+    assert_eq_db!(
+        r#"interpolate("Hello \${name}!", {name: "world"})"#,
+        r#""Hello world!""#
+    );
+    assert_eq_db!(
+        r#"interpolate("\{name}\€ and \£{n}", {name: "Lu", n: 2})"#,
+        r#""Lu and 2""#
+    );
+    assert_eq_db!(
+        r#"interpolate("\{person$name} says hi", {person: {name: "Jo"}})"#,
+        r#""Jo says hi""#
+    );
+    assert_eq_db!(
+        r#"interpolate("Hi, \{person$name}!", {person: {name: "Jo"}})"#,
+        r#""Hi, Jo!""#
+    );
+    // missing bindings are their own names
+    assert_eq_db!(r#"interpolate("Hello \${name}!", {})"#, r#""Hello name!""#);
+    assert_eq_db!(r#"interpolate("\{person$name}", {})"#, r#""undefined""#);
+    assert!(eval(r#"interpolate("oops \${", {})"#).is_err());
+    assert!(eval(r#"interpolate("hi", 5)"#).is_err());
+
+    assert_eq!(
+        Value::from("${a}-{b}€").interpolate(&eval("{a: 1, b: 2}").unwrap()),
+        Ok(Value::from("1-2"))
+    );
+}
+
+#[test]
+fn method_calls() {
+    assert_eq_db!("16.sqrt()", "4");
+    assert_eq_db!("2.25.sqrt()", "1.5");
+    assert_eq_db!("16.sqrt().sqrt()", "2");
+    assert_eq_db!(r#""abc".char_at(0)"#, r#""a""#);
+    assert_eq_db!(r#""hello".count("l")"#, "2");
+    assert_eq_db!(r#""hello".char_at(;1).ord()"#, "111");
+    assert_eq_db!("const const x = 9! x.sqrt()", "3");
+    assert_eq_db!(
+        "const const obj = {inner: {n: 25}}! obj.inner.n.sqrt()",
+        "5"
+    );
+    assert_eq_db!("[1, 2, 3].sum()", "6");
+    // objects call their own members first
+    assert_eq_db!("const const obj = {sqrt: (x) -> {x + 1}}! obj.sqrt(1)", "2");
+    // a method on each side of an operator
+    assert_eq_db!("4.sqrt() + 9.sqrt()", "5");
+}
+
+#[test]
+fn replace() {
+    assert_eq_db!(r#"replace("a-b-c", "-", "+")"#, r#""a+b+c""#);
+    assert_eq_db!(r#"replace("banana", "ana", "o")"#, r#""bona""#);
+    assert_eq_db!(r#"replace("abc", "", "x")"#, r#""abc""#);
+    // each match goes through the function
+    assert_eq_db!(
+        r#"replace("one two one", "one", (m) -> {m + "!"})"#,
+        r#""one! two one!""#
+    );
+    assert_eq_db!(r#"replace("aXbX", "X", ord)"#, r#""a88b88""#);
+    // no matches means the function is never called
+    assert_eq_db!(r#"replace("abc", "z", (m) -> {assert(false)})"#, r#""abc""#);
+    assert_eq_db!(r#""tic tac".replace("t", "T")"#, r#""Tic Tac""#);
+}
+
+#[test]
+fn lines() {
+    assert_eq_db!("lines(\"a\nb\nc\")", r#"["a", "b", "c"]"#);
+    assert_eq_db!("lines(\"a\r\nb\r\nc\")", r#"["a", "b", "c"]"#);
+    assert_eq_db!("lines(\"a\r\nb\nc\")", r#"["a", "b", "c"]"#);
+    // a final newline doesn't start another line, but a blank line in the middle is still there
+    assert_eq_db!("lines(\"a\nb\n\")", r#"["a", "b"]"#);
+    assert_eq_db!("lines(\"a\n\nb\")", r#"["a", "", "b"]"#);
+    assert_eq_db!(r#"lines("")"#, "[]");
+    assert_eq_db!("lines(5)", "[]");
+}
+
+#[test]
+fn compiled_templates() {
+    let template = Value::compile_template("${greeting}, {who$name}!").unwrap();
+    let render = |env: &str| {
+        let Value::Object(env) = eval(env).unwrap() else {
+            panic!("`{env}` isn't an object")
+        };
+        Value::render(&template, &env)
+    };
+    assert_eq!(
+        render(r#"{greeting: "Hello", who: {name: "world"}}"#),
+        Value::from("Hello, world!")
+    );
+    assert_eq!(
+        render(r#"{greeting: "Bye", who: {name: "Jo"}}"#),
+        Value::from("Bye, Jo!")
+    );
+    assert_eq!(render("{}"), Value::from("greeting, undefined!"));
+
+    // rendering is the same as interpolating in one go
+    let env = eval("{greeting: 1, who: {name: 2}}").unwrap();
+    let Value::Object(obj) = &env else {
+        unreachable!()
+    };
+    assert_eq!(
+        Ok(Value::render(&template, obj)),
+        Value::from("${greeting}, {who$name}!").interpolate(&env)
+    );
+    assert!(Value::compile_template("oops ${").is_err());
+}
+
+#[test]
+fn bases() {
+    assert_eq_db!("to_base(255, 16)", r#""ff""#);
+    assert_eq_db!("to_base(5, 2)", r#""101""#);
+    assert_eq_db!("to_base(0, 36)", r#""0""#);
+    assert_eq_db!("to_base(;35, 36)", r#""-z""#);
+    assert_eq_db!(r#"from_base("ff", 16)"#, "255");
+    assert_eq_db!(r#"from_base("FF", 16)"#, "255");
+    assert_eq_db!(r#"from_base("-z", 36)"#, ";35");
+    for num in ["0", "35", "36", "1295", "123456789", ";987654321"] {
+        assert_eq_db!(format!("from_base(to_base({num}, 36), 36)"), num);
+    }
+    assert!(eval("to_base(10, 1)").is_err());
+    assert!(eval("to_base(10, 37)").is_err());
+    assert!(eval(r#"from_base("10", 0)"#).is_err());
+    assert!(eval(r#"from_base("12", 2)"#).is_err());
+    assert!(eval("to_base(1.5, 2)").is_err());
+}
+
+#[test]
+fn fuzzy_tolerance() {
+    assert_eq!(
+        Value::from(100.0).eq(&Value::from(105.0), 1),
+        Value::from(true)
+    );
+    assert_eq!(
+        Value::from(100.0).eq_with(&Value::from(105.0), 1, 0.01),
+        Value::from(false)
+    );
+    assert_eq!(
+        Value::from(100.0).eq_with(&Value::from(150.0), 1, 0.5),
+        Value::from(true)
+    );
+
+    let run = |tolerance: f64, src: &str| {
+        eval_with(
+            |state| state.config.borrow_mut().fuzzy_tolerance = Tolerance(tolerance),
+            src,
+        )
+    };
+    assert_eq!(run(0.1, "100 == 105"), Ok(Value::from(true)));
+    assert_eq!(run(0.1, "equals(100, 105, 1)"), Ok(Value::from(true)));
+    assert_eq!(run(0.1, "[100] == [105]"), Ok(Value::from(true)));
+    assert_eq!(run(0.01, "100 == 105"), Ok(Value::from(false)));
+    assert_eq!(run(0.01, "equals(100, 105, 1)"), Ok(Value::from(false)));
+    assert_eq!(run(0.01, "[100] == [105]"), Ok(Value::from(false)));
+    assert_eq!(run(0.01, "100 == 100"), Ok(Value::from(true)));
+    assert_eq!(run(1.0, "100 == 200"), Ok(Value::from(true)));
+    // stricter equality doesn't care
+    assert_eq!(run(1.0, "100 === 105"), Ok(Value::from(false)));
+    // settings are always equal to themselves, even with a tolerance that's `NaN`
+    let nan = Config {
+        fuzzy_tolerance: Tolerance(f64::NAN),
+        ..Config::default()
+    };
+    assert_eq!(nan, nan.clone());
+    assert_ne!(nan, Config::default());
+}
+
+#[test]
+fn checked_division() {
+    assert_eq!(
+        Value::from(5.0).checked_div(&Value::from(2.0)),
+        Ok(Value::from(2.5))
+    );
+    assert_eq!(
+        Value::from(5.0).checked_rem(&Value::from(2.0)),
+        Ok(Value::from(1.0))
+    );
+    assert!(Value::from(5.0).checked_div(&Value::from(0.0)).is_err());
+    assert!(Value::from(5.0).checked_rem(&Value::from(0.0)).is_err());
+    assert!(Value::from(f64::NAN)
+        .checked_div(&Value::from(0.0))
+        .is_err());
+    let Ok(Value::Number(nan)) = Value::from(f64::NAN).checked_div(&Value::from(2.0)) else {
+        panic!("`NaN / 2` should be a number")
+    };
+    assert!(nan.is_nan());
+    let Ok(Value::Number(nan)) = Value::from(1.0).checked_rem(&Value::from(f64::NAN)) else {
+        panic!("`1 % NaN` should be a number")
+    };
+    assert!(nan.is_nan());
+
+    let run = |src: &str| {
+        eval_with(
+            |state| state.config.borrow_mut().strict_division = true,
+            src,
+        )
+    };
+    assert_eq!(eval("1 / 0"), Ok(Value::empty_object()));
+    assert_eq!(run("6 / 3"), Ok(Value::from(2.0)));
+    assert!(run("1 / 0").is_err());
+    assert!(run("1 % 0").is_err());
+    assert!(run("var var x = 4! x /= 0").is_err());
+    assert!(run("var var y = 4! y %= 0").is_err());
+}
+
+#[test]
+fn object_order() {
+    // objects are sorted by key unless they're asked not to be
+    assert_eq_db!("keys({b: 1, a: 2, c: 3})", r#"["a", "b", "c"]"#);
+    assert!(eval("keys(5)").is_err());
+
+    let run = |src: &str| {
+        eval_with(
+            |state| state.config.borrow_mut().preserve_object_order = true,
+            src,
+        )
+    };
+    let obj = run("{zebra: 1, apple: 2, mango: 3}").unwrap();
+    let shown = obj.to_string();
+    let positions = ["zebra", "apple", "mango"].map(|key| shown.find(key).unwrap());
+    assert!(positions.is_sorted(), "{shown}");
+    assert_eq!(
+        run("keys({zebra: 1, apple: 2, mango: 3})"),
+        eval(r#"["zebra", "apple", "mango"]"#)
+    );
+    assert!(matches!(&obj, Value::Object(obj) if obj.is_ordered()));
+    // order doesn't matter for equality
+    assert_eq!(obj, eval("{apple: 2, mango: 3, zebra: 1}").unwrap());
+
+    // a repeated key stays where it was first written, and new keys go on the end
+    assert_eq!(run("keys({b: 1, a: 2, b: 3})"), eval(r#"["b", "a"]"#));
+    assert_eq!(
+        run("const var o = {b: 1, a: 2}! o.c = 3! keys(o)"),
+        eval(r#"["b", "a", "c"]"#)
+    );
+    assert_eq!(
+        run("keys({y: 0, ...{b: 1, a: 2}, x: 3})"),
+        eval(r#"["y", "b", "a", "x"]"#)
+    );
+    assert_eq!(
+        run("[k for k in keys({two: 2, one: 1})]"),
+        eval(r#"["two", "one"]"#)
+    );
+    assert_eq!(
+        obj.into_iter()
+            .map(|(key, _)| key.to_string())
+            .collect::<Vec<_>>(),
+        ["zebra", "apple", "mango"]
+    );
+}
+
+#[test]
+fn csv() {
+    assert_eq_db!(
+        r#"to_csv([{name: "Ava", age: 9}, {name: "Bo", pet: "cat"}])"#,
+        "\"age,name,pet\n9,Ava,\n,Bo,cat\""
+    );
+    assert_eq_db!(
+        "from_csv(\"name,age\nAva,9\nBo,10\n\")",
+        r#"[{name: "Ava", age: "9"}, {name: "Bo", age: "10"}]"#
+    );
+    assert_eq_db!("from_csv(\"a,b\r\n1,2\r\n\")", r#"[{a: "1", b: "2"}]"#);
+    assert_eq_db!("to_csv([])", r#""""#);
+    assert_eq_db!(r#"from_csv("")"#, "[]");
+
+    // fields with commas, quotes, or newlines are quoted
+    let row = |text: &str, n: &str| {
+        Value::Object(
+            [("text", text), ("n", n)]
+                .into_iter()
+                .map(|(key, value)| {
+                    (
+                        Value::from(key),
+                        Pointer::from(Value::from(value)).convert(VarType::VarVar),
+                    )
+                })
+                .collect(),
+        )
+    };
+    let table = Value::from(vec![
+        row("hi, there", "1"),
+        row("line\nbreak", "3"),
+        row("say \"hi\"", "4"),
+    ]);
+    let csv = table.to_csv().unwrap();
+    assert_eq!(
+        csv,
+        Value::from("n,text\n1,\"hi, there\"\n3,\"line\nbreak\"\n4,\"say \"\"hi\"\"\"")
+    );
+    assert_eq!(csv.parse_csv(), Ok(table));
+
+    assert!(eval("to_csv([1, 2])").is_err());
+    assert!(eval("from_csv(\"a,b\n1\")").is_err());
+    assert!(Value::from("a\n\"oops").parse_csv().is_err());
+}
+
+#[test]
+fn partial() {
+    assert_eq_db!(
+        "const const add = (a, b) -> {a + b}! const const add5 = partial(add, 5)! add5(3)",
+        "8"
+    );
+    assert_eq_db!(
+        "const const sub = (a, b) -> {a - b}! const const from10 = partial(sub, 10)! from10(4)",
+        "6"
+    );
+    assert_eq_db!(
+        "const const f = (a, b, c) -> {a * b + c}! const const g = partial(partial(f, 2), 3)! g(4)",
+        "10"
+    );
+    // everything bound means nothing left to pass
+    assert_eq_db!(
+        "const const add = (a, b) -> {a + b}! const const three = partial(add, 1, 2)! three()",
+        "3"
+    );
+    // bound values are captured when `partial` is called
+    assert_eq_db!(
+        "var var n = 1! const const add = (a, b) -> {a + b}! const const inc = partial(add, n)! n = 100! inc(1)",
+        "2"
+    );
+    assert_eq_db!(
+        r#"const const greet = (greeting, name) -> {greeting + ", " + name}! "Ava" |> partial(greet, "hi")"#,
+        r#""hi, Ava""#
+    );
+    assert!(eval("partial(5, 1)").is_err());
+}
+
+#[test]
+fn memoize() {
+    let fib = "var var calls = 0!
+        const const fib = (n) -> {calls += 1! if(n < 2, n, fib(n - 1) + fib(n - 2))}!";
+    assert_eq_db!(format!("{fib} fib(10)"), "55");
+    assert_eq_db!(format!("{fib} fib(10)! calls"), "177");
+    let memo_fib = "var var calls = 0!
+        const const fib = memoize((n) -> {calls += 1! if(n < 2, n, fib(n - 1) + fib(n - 2))})!";
+    assert_eq_db!(format!("{memo_fib} fib(10)"), "55");
+    // each argument is only worked out once
+    assert_eq_db!(format!("{memo_fib} fib(10)! calls"), "11");
+    assert_eq_db!(format!("{memo_fib} fib(10)! fib(10)! fib(5)! calls"), "11");
+
+    // separate wrappers have separate caches
+    assert_eq_db!(
+        "var var calls = 0! const const f = (x) -> {calls += 1! x}!
+        const const a = memoize(f)! const const b = memoize(f)! a(1)! a(1)! b(1)! calls",
+        "2"
+    );
+    // arguments are compared by value, including ones that are arrays
+    assert_eq_db!(
+        "var var calls = 0! const const f = memoize((x, y) -> {calls += 1! x})!
+        f([1, 2], 3)! f([1, 2], 3)! f([1, 2], 4)! calls",
+        "2"
+    );
+    assert!(eval("memoize(5)").is_err());
+
+    // `0` and `-0` are the same argument, so they need the same hash
+    assert_eq!(Value::from(0.0), Value::from(-0.0));
+    assert_eq!(Value::from(0.0).hashcode(), Value::from(-0.0).hashcode());
+}
+
+#[test]
+fn token_stream() {
+    use crate::parser::TokenStream;
+
+    let mut tokens = TokenStream::new(crate::lexer::tokenize("a: b").unwrap());
+    assert_eq!(tokens.peek(), Some(&Token::Ident("a".into())));
+    assert_eq!(tokens.next(), Some(Token::Ident("a".into())));
+    let after_a = tokens.position();
+    assert_eq!(tokens.next_if(|tok| *tok == Token::Comma), None);
+    assert_eq!(tokens.expect(&Token::Colon, "after `a`"), Ok(()));
+    assert_eq!(
+        tokens.expect(&Token::Colon, "after `a`"),
+        Err(String::from("Expected `:` after `a`; got ` `"))
+    );
+    assert_eq!(tokens.next(), Some(Token::Ident("b".into())));
+    assert_eq!(tokens.peek(), None);
+    assert_eq!(
+        tokens.expect(&Token::RParen, "here"),
+        Err(String::from("Expected `)` here; got the end of the file"))
+    );
+    // going back makes the same tokens come out again
+    tokens.rewind(after_a);
+    assert_eq!(tokens.remaining(), 3);
+    assert_eq!(tokens.next(), Some(Token::Colon));
+
+    // parse errors come from the same place, and say what they were parsing
+    assert_eq!(
+        eval("{a: 1, b 2}"),
+        Err(String::from("Expected `:` after object key `b`; got `2`"))
+    );
+    assert!(eval("const const x<5 = 1!")
+        .unwrap_err()
+        .starts_with("Expected `>` after lifetime value"));
+    assert_eq!(
+        eval("const const x: int[x] = 1!"),
+        Err(String::from(
+            "Expected `]` after `[` in type definition; got `x`"
+        ))
+    );
+    assert_eq!(
+        eval("const const x:Map<int,int = 1!"),
+        Err(String::from(
+            "Expected `>` at the end of type definition; got `=`"
+        ))
+    );
+}
+
+#[test]
+fn sort_by_key() {
+    let people = r#"[{name: "Cy", age: 30}, {name: "Ava", age: 9}, {name: "Bo", age: 30}, {name: "Di", age: 9}]"#;
+    // ties keep their original order
+    assert_eq_db!(
+        format!("[p.name for p in sort_by_key({people}, (p) -> {{p.age}})]"),
+        r#"["Ava", "Di", "Cy", "Bo"]"#
+    );
+    assert_eq_db!(
+        format!("[p.name for p in sort_by_key({people}, (p) -> {{p.name}})]"),
+        r#"["Ava", "Bo", "Cy", "Di"]"#
+    );
+    assert_eq_db!("sort_by_key([3, 1, 2], (x) -> {;x})", "[3, 2, 1]");
+    assert_eq_db!("sort_by_key([], (x) -> {x})", "[]");
+    // the key function is only called once per item
+    assert_eq_db!(
+        "var var calls = 0! sort_by_key([4, 2, 3, 1], (x) -> {calls += 1! x})! calls",
+        "4"
+    );
+    // any key works, even ones that `<` usually can't make sense of
+    assert_eq_db!("sort_by_key([2, 1], (x) -> {{a: x}})", "[1, 2]");
+    assert_eq_db!("sort_by_key([2, 1, 3], (x) -> {[x % 2, x]})", "[2, 1, 3]");
+    // `inf * 0` is `NaN`, which goes after every other number
+    assert_eq_db!(
+        "sort_by_key([2, 3, 1], (x) -> {inf * (x - 2)})",
+        "[1, 3, 2]"
+    );
+    assert_eq_db!(
+        "sort_by_key([`num`, 1, true], (x) -> {x})",
+        "[true, `num`, 1]"
+    );
+    assert_eq_db!("sort_by_key([1, 2], (x) -> {(y) -> {y}})", "[1, 2]");
+    // records that contain themselves sort by everything else about them
+    assert_eq_db!(
+        "const var a = {x: 1}! a.me = a! const var b = {x: 2}! b.me = b! [v.x for v in sort_by_key([b, a], (v) -> {v})]",
+        "[1, 2]"
+    );
+    assert!(eval("sort_by_key(5, (x) -> {x})").is_err());
+}
+
+#[test]
+fn take_drop_chunk() {
+    assert_eq_db!("take([1, 2, 3, 4], 2)", "[1, 2]");
+    assert_eq_db!("take([1, 2], 5)", "[1, 2]");
+    assert_eq_db!("take([1, 2], 0)", "[]");
+    assert_eq_db!("drop([1, 2, 3, 4], 1)", "[2, 3, 4]");
+    assert_eq_db!("drop([1, 2], 5)", "[]");
+    assert_eq_db!("drop([1, 2], 0)", "[1, 2]");
+    assert_eq_db!("chunk([1, 2, 3, 4, 5], 2)", "[[1, 2], [3, 4], [5]]");
+    assert_eq_db!("chunk([1, 2, 3], 3)", "[[1, 2, 3]]");
+    assert_eq_db!("chunk([1, 2], 5)", "[[1, 2]]");
+    assert_eq_db!("chunk([], 2)", "[]");
+    assert!(eval("take([1, 2], ;1)").is_err());
+    assert!(eval("drop([1, 2], 1.5)").is_err());
+    assert!(eval("chunk([1, 2], 0)").is_err());
+    assert!(eval("chunk(5, 2)").is_err());
+}
+
+#[test]
+fn reverse_graphemes() {
+    assert_eq_db!(";\"abc\"", "\"cba\"");
+    // accents stay on the letter they were on
+    assert_eq_db!(";\"cafe\u{301}s\"", "\"se\u{301}fac\"");
+    assert_eq_db!(";\"a\u{323}\u{302}b\"", "\"ba\u{323}\u{302}\"");
+    // flags and joined emoji stay in one piece
+    assert_eq_db!(
+        ";\"\u{1F1EF}\u{1F1F5}\u{1F1FA}\u{1F1F8}!\"",
+        "\"!\u{1F1FA}\u{1F1F8}\u{1F1EF}\u{1F1F5}\""
+    );
+    assert_eq_db!(
+        ";\"x\u{1F469}\u{200D}\u{1F4BB}\u{1F44D}\u{1F3FD}\"",
+        "\"\u{1F44D}\u{1F3FD}\u{1F469}\u{200D}\u{1F4BB}x\""
+    );
+    assert_eq_db!(";\"\"", "\"\"");
+}
+
+#[test]
+fn arithmetic_table() {
+    let num = || Value::Number(3.0);
+    let string = || Value::from("3");
+    let yes = || Value::from(true);
+    let arr = || Value::from(vec![Value::Number(1.0)]);
+    let undefined = Value::default;
+    let table = [
+        (
+            num(),
+            Operation::Add,
+            Value::Number(4.0),
+            Value::Number(7.0),
+        ),
+        (
+            num(),
+            Operation::Sub,
+            Value::Number(4.0),
+            Value::Number(-1.0),
+        ),
+        (
+            num(),
+            Operation::Mul,
+            Value::Number(4.0),
+            Value::Number(12.0),
+        ),
+        (
+            num(),
+            Operation::Div,
+            Value::Number(4.0),
+            Value::Number(0.75),
+        ),
+        (
+            num(),
+            Operation::Mod,
+            Value::Number(2.0),
+            Value::Number(1.0),
+        ),
+        (num(), Operation::Div, Value::Number(0.0), undefined()),
+        (num(), Operation::Mod, Value::Number(-0.0), undefined()),
+        (yes(), Operation::Add, num(), Value::Number(4.0)),
+        (
+            num(),
+            Operation::Add,
+            Value::Boolean(Boolean::Maybe),
+            Value::Number(3.5),
+        ),
+        (yes(), Operation::Sub, num(), undefined()),
+        (
+            string(),
+            Operation::Add,
+            Value::Number(4.0),
+            Value::from("34"),
+        ),
+        (string(), Operation::Add, yes(), Value::from("3true")),
+        (string(), Operation::Add, arr(), Value::from("3[1]")),
+        (num(), Operation::Add, string(), undefined()),
+        (
+            Value::from("ab"),
+            Operation::Mul,
+            Value::Number(2.5),
+            Value::from("ababa"),
+        ),
+        (
+            Value::from("ab"),
+            Operation::Mul,
+            Value::Number(-1.0),
+            Value::from("ba"),
+        ),
+        (num(), Operation::Mul, string(), undefined()),
+        (string(), Operation::Sub, num(), undefined()),
+        (string(), Operation::Div, num(), undefined()),
+        (arr(), Operation::Sub, arr(), undefined()),
+        (undefined(), Operation::Mod, num(), undefined()),
+        (
+            num(),
+            Operation::AddEq,
+            Value::Number(1.0),
+            Value::Number(4.0),
+        ),
+        (
+            string(),
+            Operation::MulEq,
+            Value::Number(2.0),
+            Value::from("33"),
+        ),
+    ];
+    for (lhs, op, rhs, expected) in table {
+        let desc = format!("{lhs:?} {op} {rhs:?}");
+        assert_eq!(Value::arith(op, lhs, rhs), Ok(expected), "{desc}");
+    }
+    assert_eq!(num() + num(), Value::Number(6.0));
+    assert_eq!(string() * Value::Number(2.0), Value::from("33"));
+    assert!(Value::arith(Operation::Lt, num(), num()).is_err());
+}
+
+#[test]
+fn with_key() {
+    assert_eq_db!(
+        "const const a = {x: 1}! const const b = with_key(a, \"y\", 2)! [a, b]",
+        "[{x: 1}, {x: 1, y: 2}]"
+    );
+    // overwriting a key
+    assert_eq_db!(
+        "const const a = {x: 1}! const const b = with_key(a, \"x\", 2)! [a.x, b.x]",
+        "[1, 2]"
+    );
+    // changing the copy doesn't change the original, even when it's frozen
+    let setup = "const var a = {x: 1}! freeze(a)! const var b = with_key(a, \"y\", 2)! b.x = 5!";
+    assert_eq_db!(format!("{setup} a.x"), "1");
+    assert_eq_db!(format!("{setup} [b.x, b.y]"), "[5, 2]");
+    assert!(eval("with_key(5, \"x\", 1)").is_err());
+}
+
+#[test]
+fn without_key() {
+    let setup = "const var a = {x: 1, y: 2, z: 3}!";
+    assert_eq_db!(
+        format!("{setup} const const b = without_key(a, \"y\")! [a, b]"),
+        "[{x: 1, y: 2, z: 3}, {x: 1, z: 3}]"
+    );
+    assert_eq_db!(format!("{setup} without_key(a, [\"x\", \"z\"])"), "{y: 2}");
+    // keys that aren't there don't matter
+    assert_eq_db!(
+        format!("{setup} without_key(a, \"w\")"),
+        "{x: 1, y: 2, z: 3}"
+    );
+    assert_eq_db!(
+        format!("{setup} without_key(a, [\"w\", \"x\"])"),
+        "{y: 2, z: 3}"
+    );
+    assert!(eval("without_key(5, \"x\")").is_err());
+}
+
+#[test]
+fn invert() {
+    assert_eq_db!(
+        "invert({a: true, b: false, c: maybe, d: 5, e: \"true\", f: [true]})",
+        "{a: false, b: true, c: maybe, d: 5, e: \"true\", f: [true]}"
+    );
+    assert_eq_db!(
+        "invert({dark: true, sound: {music: false, effects: true, volume: 3}})",
+        "{dark: false, sound: {music: true, effects: false, volume: 3}}"
+    );
+    // the original is left alone
+    assert_eq_db!(
+        "const const flags = {on: true}! const const flipped = invert(flags)! [flags.on, flipped.on]",
+        "[true, false]"
+    );
+    // an object inside of itself is inverted when it first comes up, and after that it's left as it is
+    assert_eq_db!(
+        "const var a = {on: true}! a.me = a! const const flipped = invert(a)! [flipped.on, flipped.me.on, flipped.me.me.on]",
+        "[false, false, true]"
+    );
+    assert!(eval("invert(true)").is_err());
+}
+
+#[test]
+fn function_display() {
+    let add = eval("(a, b) -> {a + b}").unwrap();
+    let shown = add.to_string();
+    assert_eq!(shown, "(a, b) -> {a + b}");
+    // the display can be read back in as the same function
+    assert_eq_db!(format!("const const add = {shown}! add(2, 3)"), "5");
+    assert_eq!(
+        eval("(x) -> {const const y = x! y}").unwrap().to_string(),
+        "(x) -> {\n    const const y = x!\n    y\n}"
+    );
+    // the debug form shows the syntax tree instead
+    let debug = add.to_pretty_debug();
+    assert!(
+        debug.starts_with("Function([\"a\", \"b\"], Operation("),
+        "{debug}"
+    );
+    assert_ne!(debug, shown);
+}
+
+#[test]
+fn syntax_printing() {
+    let src = "(n, items) -> {
+        var var total = 0!
+        if (n > 0, {
+            total += n*2!
+            total++!
+        })!
+        [item.name for item in items if item.shown]!
+        ;total?
+    }";
+    let printed = eval(src).unwrap().to_string();
+    assert_eq!(
+        printed,
+        "(n, items) -> {
+    var var total = 0!
+    if(n > 0, {
+        total += (n * 2)!
+        total++!
+    })!
+    [item.name for item in items if item.shown]!
+    ;total?
+}"
+    );
+    // printing it again after parsing what was printed doesn't change anything
+    assert_eq!(eval(&printed).unwrap().to_string(), printed);
+    let syntax = crate::parser::parse(crate::lexer::tokenize("{f(x.y, 2)*3!}").unwrap()).unwrap();
+    assert_eq!(format!("{syntax:#}"), "f(x.y, 2) * 3!");
+}
+
+#[test]
+fn finite_and_nan() {
+    assert_eq_db!("is_finite(3.5)", "true");
+    assert_eq_db!("is_finite(inf)", "false");
+    assert_eq_db!("is_finite(-inf)", "false");
+    assert_eq_db!("is_finite(inf - inf)", "false");
+    assert_eq_db!("is_nan(inf - inf)", "true");
+    assert_eq_db!("is_nan(sqrt(;1))", "true");
+    assert_eq_db!("is_nan(inf)", "false");
+    assert_eq_db!("is_nan(0)", "false");
+    assert_eq_db!("is_finite(\"3\")", "maybe");
+    assert_eq_db!("is_nan(\"hello\")", "maybe");
+}
+
+#[test]
+fn apply_defaults() {
+    assert_eq_db!(
+        "apply_defaults({volume: 3}, {volume: 11, theme: \"dark\"})",
+        "{volume: 3, theme: \"dark\"}"
+    );
+    assert_eq_db!(
+        "apply_defaults({sound: {music: false}}, {sound: {music: true, volume: 11}, dark: true})",
+        "{sound: {music: false, volume: 11}, dark: true}"
+    );
+    // a key that isn't an object on one side is kept as it is
+    assert_eq_db!(
+        "apply_defaults({sound: 0}, {sound: {volume: 11}})",
+        "{sound: 0}"
+    );
+    assert_eq_db!(
+        "apply_defaults({a: 1, b: {c: 2}}, {a: 5, b: {c: 6}})",
+        "{a: 1, b: {c: 2}}"
+    );
+    // neither object changes
+    assert_eq_db!(
+        "const const config = {a: 1}! const const defaults = {b: 2}! apply_defaults(config, defaults)! [config, defaults]",
+        "[{a: 1}, {b: 2}]"
+    );
+    // objects inside of themselves get their defaults once, and after that they're left as they are
+    assert_eq_db!(
+        "const var a = {x: 1}! a.me = a! const var b = {y: 2}! b.me = b! const const filled = apply_defaults(a, b)! [filled.y, filled.me.y, keys(filled.me.me)]",
+        "[2, 2, [`me`, `x`]]"
+    );
+    assert!(eval("apply_defaults(5, {a: 1})").is_err());
+}
+
+#[test]
+fn pick_and_omit() {
+    let setup = "const const user = {name: \"Ava\", age: 9, admin: false}!";
+    assert_eq_db!(
+        format!("{setup} pick(user, [\"name\", \"age\"])"),
+        "{name: \"Ava\", age: 9}"
+    );
+    // keys that aren't there are just left out
+    assert_eq_db!(
+        format!("{setup} pick(user, [\"name\", \"email\"])"),
+        "{name: \"Ava\"}"
+    );
+    assert_eq_db!(
+        format!("{setup} omit(user, [\"age\", \"admin\"])"),
+        "{name: \"Ava\"}"
+    );
+    assert_eq_db!(
+        format!("{setup} omit(user, [\"email\"])"),
+        "{name: \"Ava\", age: 9, admin: false}"
+    );
+    assert_eq_db!(
+        format!("{setup} pick(user, [\"age\"])! user"),
+        "{name: \"Ava\", age: 9, admin: false}"
+    );
+    assert!(eval("pick({a: 1}, \"a\")").is_err());
+    assert!(eval("omit({a: 1}, \"a\")").is_err());
+}
+
+#[test]
+fn bool_to_number() {
+    assert_eq_db!("bool_to_number(false)", "0");
+    assert_eq_db!("bool_to_number(maybe)", "0.5");
+    assert_eq_db!("bool_to_number(true)", "1");
+    // the same numbers arithmetic uses
+    assert_eq_db!("bool_to_number(maybe) + 1", "maybe + 1");
+    assert!(eval("bool_to_number(1)").is_err());
+}
+
+#[test]
+fn parse_recovering() {
+    let src = "const const a = 1!\nconst const b = -5!\nconst const c = 3!\nprint(c, -2)!\n";
+    let errors = crate::parser::parse_recovering(src, &Config::default()).unwrap_err();
+    assert_eq!(
+        errors,
+        [
+            "`-` can only negate `inf`; use `;` to negate anything else at 2:18",
+            "`-` can only negate `inf`; use `;` to negate anything else at 4:11",
+        ]
+    );
+    // a file without errors parses the same as it does normally
+    let good = "const const a = 1!\nprint(a)!";
+    assert_eq!(
+        crate::parser::parse_recovering(good, &Config::default()),
+        Ok(crate::parser::parse(crate::lexer::tokenize(&format!("{{{good}}}")).unwrap()).unwrap())
+    );
+    // lexer errors still stop everything
+    assert_eq!(
+        crate::parser::parse_recovering("\"oops", &Config::default())
+            .unwrap_err()
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn swap_case() {
+    assert_eq_db!("swap_case(\"Hello, World!\")", "\"hELLO, wORLD!\"");
+    assert_eq_db!("swap_case(\"ÀéΣω\")", "\"àÉσΩ\"");
+    assert_eq_db!("swap_case(\"straße\")", "\"STRASSE\"");
+    assert_eq_db!("swap_case(\"123 你好\")", "\"123 你好\"");
+    assert_eq_db!("swap_case(5)", "5");
+    assert_eq_db!("swap_case(maybe)", "maybe");
+}
+
+#[test]
+fn capitalize_and_title_case() {
+    assert_eq_db!("capitalize(\"hELLO\")", "\"Hello\"");
+    assert_eq_db!("capitalize(\"the QUICK fox\")", "\"The quick fox\"");
+    assert_eq_db!("capitalize(\"  élan\")", "\"  Élan\"");
+    assert_eq_db!("capitalize(\"\")", "\"\"");
+    assert_eq_db!("title_case(\"hello\")", "\"Hello\"");
+    assert_eq_db!(
+        "title_case(\"the QUICK  brown-fox\")",
+        "\"The Quick  Brown-fox\""
+    );
+    assert_eq_db!(
+        "title_case(\"  leading\tand trailing \")",
+        "\"  Leading\tAnd Trailing \""
+    );
+    assert_eq_db!("title_case(12)", "12");
+}
+
+#[test]
+fn byte_len() {
+    assert_eq_db!("len(\"hello\")", "5");
+    assert_eq_db!("byte_len(\"hello\")", "5");
+    assert_eq_db!("len(\"héllo\")", "5");
+    assert_eq_db!("byte_len(\"héllo\")", "6");
+    assert_eq_db!("len(\"日本\")", "2");
+    assert_eq_db!("byte_len(\"日本\")", "6");
+    assert_eq_db!("len(\"🦀\")", "1");
+    assert_eq_db!("byte_len(\"🦀\")", "4");
+    assert_eq_db!("len([1, 2, 3])", "3");
+    assert_eq_db!("len({a: 1})", "1");
+    // repeating part of a string counts characters too
+    assert_eq_db!("\"日本語\" * 1.7", "\"日本語日本\"");
+    assert!(eval("len(5)").is_err());
+    assert!(eval("byte_len([1])").is_err());
+}
+
+#[test]
+fn newline_statements() {
+    let config = Config {
+        newline_statements: true,
+        ..Config::default()
+    };
+    let parse = |src: &str, config: &Config| {
+        crate::parser::parse(crate::lexer::tokenize_with(&format!("{{{src}}}"), config).unwrap())
+    };
+    // the last line of a block doesn't get a `!`, so it's still what the block gives back
+    let with_bangs = "const const double = (x) -> {
+    x * 2
+}!
+var var total = 1!
+total += double(
+    3
+)!
+print(total)!
+total";
+    let with_newlines = "const const double = (x) -> {
+    x * 2
+}
+var var total = 1
+total += double(
+    3
+)
+print(total)
+total";
+    assert_eq!(
+        parse(with_newlines, &config),
+        parse(with_bangs, &Config::default())
+    );
+    // `!`s still work, and don't get doubled up
+    assert_eq!(
+        parse(with_bangs, &config),
+        parse(with_bangs, &Config::default())
+    );
+    // a line that isn't finished keeps going
+    assert_eq!(
+        parse("const const a = 1 +\n    2\na", &config),
+        parse("const const a = 1 +\n    2!\na", &Config::default())
+    );
+    // without the flag, a newline is just whitespace
+    assert_ne!(
+        parse(with_newlines, &Config::default()),
+        parse(with_bangs, &Config::default())
+    );
+}
+
+#[test]
+fn arity_and_params() {
+    assert_eq_db!("arity(() -> {1})", "0");
+    assert_eq_db!("params(() -> {1})", "[]");
+    assert_eq_db!("arity((a, b) -> {a + b})", "2");
+    assert_eq_db!("params((a, b) -> {a + b})", r#"["a", "b"]"#);
+    assert_eq_db!("const const f = (x, y, z) -> {x}! arity(f)", "3");
+    // these look through wrappers that build new functions
+    assert_eq_db!("arity(partial((a, b, c) -> {a}, 1))", "2");
+    assert_eq_db!("params(memoize((n) -> {n}))", r#"["n"]"#);
+    assert!(eval("arity(print)").is_err());
+    assert!(eval("params(5)").is_err());
+    let func = eval("(a) -> {a}").unwrap();
+    assert_eq!(func.as_function().map(|(params, _)| params.len()), Some(1));
+    assert!(Value::from(1.0).as_function().is_none());
+}
+
+#[test]
+fn array_add() {
+    assert_eq_db!("const const list = [1, 2]! list + 3", "[1, 2, 3]");
+    assert_eq_db!("const const list = [1, 2]! 0 + list", "[0, 1, 2]");
+    assert_eq_db!("[1, 2] + [3, 4]", "[1, 2, 3, 4]");
+    // an array on the other side is one new item, not more to join
+    assert_eq_db!("[1, 2] + [[3]]", "[1, 2, [3]]");
+    assert_eq_db!("[] + []", "[]");
+    assert_eq_db!("[1] + {a: 2}", "[1, {a: 2}]");
+    // strings still win on the left
+    assert_eq_db!(r#""a" + [1]"#, r#""a[1]""#);
+    // the new array doesn't share items with the old one
+    assert_eq_db!(
+        "const const list = [1, 2]! var var more = list + 3! more.0 = 5! list.0",
+        "2"
+    );
+}
+
+#[test]
+fn insert_and_remove_at() {
+    assert_eq_db!("insert([1, 2, 3], ;1, 0)", "[0, 1, 2, 3]");
+    assert_eq_db!("insert([1, 2, 3], 0, 5)", "[1, 5, 2, 3]");
+    assert_eq_db!("insert([1, 2, 3], 2, 4)", "[1, 2, 3, 4]");
+    assert_eq_db!("insert([], ;1, 1)", "[1]");
+    assert!(eval("insert([1, 2, 3], 3, 4)").is_err());
+    assert!(eval("insert([1, 2, 3], ;2, 4)").is_err());
+    assert!(eval("insert([1, 2, 3], 0.5, 4)").is_err());
+    assert_eq_db!("remove_at([1, 2, 3], ;1)", "[2, 3]");
+    assert_eq_db!("remove_at([1, 2, 3], 0)", "[1, 3]");
+    assert_eq_db!("remove_at([1, 2, 3], 1)", "[1, 2]");
+    assert!(eval("remove_at([1, 2, 3], 2)").is_err());
+    assert!(eval("remove_at([1, 2, 3], ;2)").is_err());
+    assert!(eval("remove_at([], ;1)").is_err());
+    // the original array stays the same
+    assert_eq_db!(
+        "const const list = [1, 2]! remove_at(list, 0)! insert(list, ;1, 0)! list",
+        "[1, 2]"
+    );
+    assert!(eval("insert(5, ;1, 1)").is_err());
+}
+
+#[test]
+fn flat_map() {
+    assert_eq_db!(
+        "flat_map([1, 2, 3], (n) -> {range(n)})",
+        "[0, 0, 1, 0, 1, 2]"
+    );
+    assert_eq_db!("flat_map([0, 2, 0], (n) -> {range(n)})", "[0, 1]");
+    assert_eq_db!("flat_map([1, 2], (n) -> {[]})", "[]");
+    assert_eq_db!("flat_map([], (n) -> {[n, n]})", "[]");
+    // only one level gets flattened
+    assert_eq_db!("flat_map([1, 2], (n) -> {[[n]]})", "[[1], [2]]");
+    assert_eq_db!(
+        r#"flat_map(["a", "bc"], (s) -> {[s, s]})"#,
+        r#"["a", "a", "bc", "bc"]"#
+    );
+    assert!(eval("flat_map([1, 2], (n) -> {n})").is_err());
+    assert!(eval("flat_map(5, (n) -> {[n]})").is_err());
+}
+
+#[test]
+fn strict_keys() {
+    // objects and functions can be keys normally
+    assert_eq_db!("keys(to_object([[[1], 2]]))", "[[1]]");
+    assert!(Value::from("a").is_scalar_key());
+    assert!(!eval("[1]").unwrap().is_scalar_key());
+    assert!(eval("const var o = {a: 1}! o.[1]").is_ok());
+
+    let run = |src: &str| eval_with(|state| state.config.borrow_mut().strict_keys = true, src);
+    assert_eq!(
+        run(r#"keys(to_object([["a", 1], [2, 2], [true, 3], [if, 4]]))"#),
+        eval(r#"[true, "a", 2, if]"#)
+    );
+    assert_eq!(
+        run("const var o = {}! o.name = 5! o.name"),
+        Ok(Value::from(5.0))
+    );
+    assert_eq!(run("keys(count([1, 1, 2]))"), eval("[1, 2]"));
+    assert!(run("to_object([[{a: 1}, 2]])").is_err());
+    assert!(run("to_object([[(x) -> {x}, 2]])").is_err());
+    assert!(run("const var o = {a: 1}! o.[1]").is_err());
+    assert!(run("with_key({a: 1}, [1], 2)").is_err());
+    assert!(run("group_by([1, 2], (n) -> {[n]})").is_err());
+    assert!(run("count([[1], [1]])").is_err());
+    assert!(run("to_object([[(x) -> {x}, 1], [(y) -> {y}, 2]])").is_err());
+    assert!(run(r#"coerce([[[1], 2]], "object")"#).is_err());
+    assert_eq!(
+        run(r#"coerce([[[1], 2]], "string")"#),
+        eval(r#""[[[1], 2]]""#)
+    );
+}
+
+#[test]
+fn sample() {
+    let source = "seed(5)! [sample(range(100)), sample(range(100), 3)]";
+    assert_eq!(eval(source), eval(source));
+    // a different seed picks different items
+    assert_ne!(
+        eval(source),
+        eval("seed(6)! [sample(range(100)), sample(range(100), 3)]")
+    );
+    for _ in 0..20 {
+        let Ok(Value::Number(pick)) = eval("sample([1, 2, 3])") else {
+            panic!("`sample` should give back a number");
+        };
+        assert!([1.0, 2.0, 3.0].contains(&pick));
+        // picks are all different
+        let picks = eval("sample(range(10), 4)").unwrap();
+        let Value::Array(picks) = picks else {
+            panic!("`sample` with a count should give back an array");
+        };
+        let mut picks = picks.iter().map(Pointer::clone_inner).collect::<Vec<_>>();
+        picks.sort();
+        picks.dedup();
+        assert_eq!(picks.len(), 4);
+    }
+    assert_eq_db!("sample([])", "undefined");
+    assert_eq_db!("sample([], 2)", "[]");
+    assert_eq_db!("sample([1, 2, 3], 0)", "[]");
+    // asking for more than there are gives all of them, shuffled
+    let all = eval("seed(1)! sample([1, 2, 3, 4, 5], 10)").unwrap();
+    let Value::Array(all) = all else {
+        panic!("`sample` with a count should give back an array");
+    };
+    let mut all = all.iter().map(Pointer::clone_inner).collect::<Vec<_>>();
+    all.sort();
+    assert_eq!(Value::from(all), eval("[1, 2, 3, 4, 5]").unwrap());
+    assert!(eval("sample(5)").is_err());
+    assert!(eval("sample([1, 2], ;1)").is_err());
+}
+
+#[test]
+fn shuffle() {
+    let source = "seed(9)! shuffle(range(20))";
+    assert_eq!(eval(source), eval(source));
+    assert_ne!(eval(source), eval("seed(10)! shuffle(range(20))"));
+    // with 20 items, staying in order is very unlikely
+    assert_ne!(eval(source), eval("range(20)"));
+    for _ in 0..20 {
+        let Ok(Value::Array(shuffled)) = eval("shuffle([1, 1, 2, 3, `a`])") else {
+            panic!("`shuffle` should give back an array");
+        };
+        let mut items = shuffled
+            .iter()
+            .map(Pointer::clone_inner)
+            .collect::<Vec<_>>();
+        items.sort();
+        assert_eq!(Value::from(items), eval("[`a`, 1, 1, 2, 3]").unwrap());
+    }
+    assert_eq_db!("shuffle([])", "[]");
+    assert_eq_db!("shuffle([1])", "[1]");
+    // the original stays the same
+    assert_eq_db!(
+        "const const arr = [1, 2, 3]! shuffle(arr)! arr",
+        "[1, 2, 3]"
+    );
+    assert!(eval("shuffle(5)").is_err());
+}
+
+#[test]
+fn windows() {
+    assert_eq_db!("windows([1, 2, 3, 4], 2)", "[[1, 2], [2, 3], [3, 4]]");
+    assert_eq_db!("windows([1, 2, 3, 4], 4)", "[[1, 2, 3, 4]]");
+    assert_eq_db!("windows([1, 2, 3, 4], 1)", "[[1], [2], [3], [4]]");
+    assert_eq_db!("windows([1, 2], 3)", "[]");
+    assert_eq_db!("windows([], 1)", "[]");
+    assert!(eval("windows([1, 2], 0)").is_err());
+    assert!(eval("windows([1, 2], ;1)").is_err());
+    assert!(eval("windows([1, 2], 1.5)").is_err());
+    assert!(eval("windows(5, 1)").is_err());
+}
+
+#[test]
+fn clamp_width() {
+    assert_eq_db!(r#"clamp_width("hi", 8)"#, r#""hi""#);
+    assert_eq_db!(r#"clamp_width("exactly8", 8)"#, r#""exactly8""#);
+    assert_eq_db!(r#"clamp_width("hello world", 8)"#, r#""hello w…""#);
+    assert_eq_db!(r#"clamp_width("hello", 1)"#, r#""…""#);
+    assert_eq_db!(r#"clamp_width("hello", 0)"#, r#""""#);
+    assert_eq_db!(r#"clamp_width("", 0)"#, r#""""#);
+    // characters count as 1 unless wide ones are asked for
+    assert_eq_db!(r#"clamp_width("日本語です", 5)"#, r#""日本語です""#);
+    assert_eq_db!(r#"clamp_width("日本語です", 5, true)"#, r#""日本…""#);
+    assert_eq_db!(r#"clamp_width("日本語です", 10, true)"#, r#""日本語です""#);
+    assert_eq_db!(r#"clamp_width("a日b", 3, true)"#, r#""a…""#);
+    assert!(eval(r#"clamp_width("hello", 1.5)"#).is_err());
+    assert!(eval(r#"clamp_width("hello", ;1)"#).is_err());
+    assert!(eval("clamp_width(5, 1)").is_err());
+}
+
+#[test]
+fn debug() {
+    let setup = r#"const var original = {name: "Ava"}! const var alias = original! const var copy = {...original}!"#;
+    let Ok(Value::Array(shown)) = eval(format!(
+        "{setup} [debug(original), debug(alias), debug(copy)]"
+    )) else {
+        panic!("`debug` should give back strings");
+    };
+    let [original, alias, copy] = &shown.iter().map(Pointer::clone_inner).collect::<Vec<_>>()[..]
+    else {
+        panic!("there should be three strings");
+    };
+    // aliases share every address, but copies don't
+    assert_eq!(original, alias);
+    assert_ne!(original, copy);
+    let original = original.to_string();
+    assert!(original.starts_with("const var @0x"), "{original}");
+    assert!(original.contains(r#"Object { "name": "#), "{original}");
+    assert!(original.contains(r#"String("Ava")"#), "{original}");
+    // displaying doesn't show any of that
+    assert_eq_db!(
+        format!("{setup} [original, copy]"),
+        r#"[{name: "Ava"}, {name: "Ava"}]"#
+    );
+
+    let Ok(Value::String(shown)) = eval("debug([1, true, `a`, if])") else {
+        panic!("`debug` should give back a string");
+    };
+    for tag in [
+        "Array [",
+        "Number(1)",
+        "Boolean(true)",
+        r#"String("a")"#,
+        "Keyword(if)",
+    ] {
+        assert!(shown.contains(tag), "{shown}");
+    }
+    let Ok(Value::String(shown)) = eval("const var frozen = {a: 1}! freeze(frozen)! debug(frozen)")
+    else {
+        panic!("`debug` should give back a string");
+    };
+    assert!(shown.contains("frozen Object"), "{shown}");
+}
+
+#[test]
+fn truthy_count() {
+    assert_eq_db!(
+        r#"truthy_count([true, false, maybe, 1, 0, 0.5, "yes", "", [1]])"#,
+        "{true: 4, false: 3, maybe: 2}"
+    );
+    assert_eq_db!(
+        "truthy_count({a: true, b: maybe, c: maybe})",
+        "{true: 1, false: 0, maybe: 2}"
+    );
+    assert_eq_db!("truthy_count([])", "{true: 0, false: 0, maybe: 0}");
+    assert!(eval("truthy_count(true)").is_err());
+}
+
+#[test]
+fn vote() {
+    assert_eq_db!("vote([true, true, false])", "true");
+    assert_eq_db!(r#"vote([0, "", false, true])"#, "false");
+    assert_eq_db!("vote([maybe, maybe, true, false])", "maybe");
+    assert_eq_db!("vote({a: true, b: 1, c: maybe})", "true");
+    // ties are `maybe`
+    assert_eq_db!("vote([true, false])", "maybe");
+    assert_eq_db!("vote([true, true, false, false, maybe])", "maybe");
+    assert_eq_db!("vote([true, maybe])", "maybe");
+    assert_eq_db!("vote([maybe, maybe, maybe])", "maybe");
+    assert_eq_db!("vote([])", "maybe");
+    assert!(eval("vote(5)").is_err());
+}
+
+#[test]
+fn implies() {
+    let table = [
+        ("true", "true", "true"),
+        ("true", "maybe", "maybe"),
+        ("true", "false", "false"),
+        ("maybe", "true", "true"),
+        ("maybe", "maybe", "maybe"),
+        ("maybe", "false", "maybe"),
+        ("false", "true", "true"),
+        ("false", "maybe", "true"),
+        ("false", "false", "true"),
+    ];
+    for (lhs, rhs, expected) in table {
+        assert_eq_db!(format!("implies({lhs}, {rhs})"), expected);
+    }
+    // other values count as what they're truthy as
+    assert_eq_db!(r#"implies(1, "")"#, "false");
+    assert_eq_db!("implies(0, 0)", "true");
+    assert!(eval("implies(true)").is_err());
+}
+
+#[test]
+fn coerce() {
+    assert_eq_db!(r#"coerce(" 42 ", "number")"#, "42");
+    assert_eq_db!(r#"coerce(maybe, "number")"#, "0.5");
+    assert_eq_db!(r#"coerce(7, "number")"#, "7");
+    assert!(eval(r#"coerce("hello", "number")"#).is_err());
+    assert!(eval(r#"coerce([1], "number")"#).is_err());
+
+    assert_eq_db!(r#"coerce(42, "string")"#, r#""42""#);
+    assert_eq_db!(r#"coerce([1, true], "string")"#, r#""[1, true]""#);
+
+    assert_eq_db!(r#"coerce(0.5, "boolean")"#, "maybe");
+    assert_eq_db!(r#"coerce("", "boolean")"#, "false");
+    assert_eq_db!(r#"coerce([1], "boolean")"#, "true");
+
+    assert_eq_db!(r#"coerce([["a", 1], ["b", 2]], "object")"#, "{a: 1, b: 2}");
+    assert_eq_db!(r#"coerce({a: 1}, "object")"#, "{a: 1}");
+    assert!(eval(r#"coerce(5, "object")"#).is_err());
+    assert!(eval(r#"coerce([1, 2], "object")"#).is_err());
+
+    assert_eq_db!(
+        r#"coerce({a: 1, b: 2}, "array")"#,
+        r#"[["a", 1], ["b", 2]]"#
+    );
+    assert_eq_db!(r#"coerce("hi", "array")"#, r#"["h", "i"]"#);
+    assert_eq_db!(r#"coerce([1], "array")"#, "[1]");
+    assert!(eval(r#"coerce(5, "array")"#).is_err());
+    assert!(eval(r#"coerce(true, "array")"#).is_err());
+
+    // round trips
+    assert_eq_db!(
+        r#"coerce(coerce({a: 1, b: 2}, "array"), "object")"#,
+        "{a: 1, b: 2}"
+    );
+    assert!(eval(r#"coerce(5, "integer")"#).is_err());
+    assert!(eval("coerce(5, 5)").is_err());
+}
+
+#[test]
+fn object_ordering() {
+    assert_eq_db!(r#"{age: 9, name: "Sam"} < {age: 10, name: "Ava"}"#, "true");
+    assert_eq_db!(r#"{age: 9, name: "Sam"} < {age: 9, name: "Ava"}"#, "false");
+    // the first key that's different decides, not the first value
+    assert_eq_db!("{a: 5} < {b: 1}", "true");
+    assert_eq_db!("{a: 1} < {a: 1, b: 1}", "true");
+    assert_eq_db!("{a: 1} <= {a: 1}", "true");
+
+    let records = [
+        r#"{name: "Bo", age: 30}"#,
+        r#"{name: "Ava", age: 9}"#,
+        r#"{name: "Cy", age: 9}"#,
+        r#"{age: 9, name: "Ava"}"#,
+        "{age: 30, id: 1}",
+    ]
+    .map(|record| eval(record).unwrap());
+    let mut sorted = records.iter().enumerate().collect::<Vec<_>>();
+    // `sort_by_key` is stable, so records that are equal stay in the order they started in
+    sorted.sort_by_key(|(_, record)| *record);
+    // `age` comes before `id` and `name`, so it decides first. Ties on `age` go on to the next key
+    assert_eq!(
+        sorted.iter().map(|(idx, _)| *idx).collect::<Vec<_>>(),
+        [1, 3, 2, 4, 0]
+    );
+    // the same entries sort the same, whatever order an object keeps its keys in
+    let ordered = eval_with(
+        |state| state.config.borrow_mut().preserve_object_order = true,
+        r#"{name: "Ava", age: 10}"#,
+    )
+    .unwrap();
+    assert!(ordered > records[1]);
+    assert!(ordered < records[0]);
+
+    // values that contain themselves are ordered by everything else about them
+    let setup = "const var a = {x: 1}! a.me = a! const var b = {x: 1}! b.me = b! const var c = {x: 2}! c.me = c!";
+    assert_eq_db!(
+        format!("{setup} [a < b, a <= b, a > b, a < c, c > b]"),
+        "[false, true, false, true, true]"
+    );
+    assert_eq_db!(
+        format!("{setup} len(group_by([a, b, c], (v) -> {{v}}))"),
+        "2"
+    );
+}
+
+#[test]
+fn mixed_ordering() {
+    let mut values = [
+        "(y) -> {y}",
+        "nan",
+        "\"b\"",
+        "[1, 2]",
+        "2",
+        "(x) -> {x}",
+        "{a: 1}",
+        "true",
+        ";inf",
+        "nan",
+        "\"a\"",
+    ]
+    .map(|src| eval(src).unwrap());
+    values.sort();
+    assert_eq!(
+        values
+            .iter()
+            .map(|val| format!("{val:?}"))
+            .collect::<Vec<_>>(),
+        [
+            "true",
+            "\"a\"",
+            "\"b\"",
+            "-inf",
+            "2",
+            "NaN",
+            "NaN",
+            "object { a: var var (1) }",
+            "[1, 2]",
+            "(x) -> {x}",
+            "(y) -> {y}",
+        ]
+    );
+    // every pair has to agree with itself both ways, or maps and sorts can go wrong
+    for lhs in &values {
+        for rhs in &values {
+            assert_eq!(lhs.cmp(rhs), rhs.cmp(lhs).reverse());
+        }
+    }
+}
+
+#[test]
+fn query_strings() {
+    assert_eq_db!(
+        r#"to_query_string({name: "Ava", age: 9})"#,
+        r#""age=9&name=Ava""#
+    );
+    assert_eq_db!(
+        r#"to_query_string({q: "cats & dogs", "a=b": "100%", e: "é"})"#,
+        r#""a%3Db=100%25&e=%C3%A9&q=cats%20%26%20dogs""#
+    );
+    assert_eq!(Value::empty_object().to_query_string(), Ok(Value::from("")));
+    assert!(eval("to_query_string({a: {b: 1}})").is_err());
+    assert!(eval("to_query_string({a: [1]})").is_err());
+    assert!(eval("to_query_string(5)").is_err());
+
+    assert_eq_db!(
+        r#"from_query_string("?q=cats+%26+dogs&page=2")"#,
+        r#"{q: "cats & dogs", page: "2"}"#
+    );
+    assert_eq_db!(
+        r#"from_query_string("flag&a=1&a=2&&e=%c3%a9")"#,
+        r#"{flag: "", a: "2", e: "é"}"#
+    );
+    assert_eq_db!(r#"keys(from_query_string(""))"#, "[]");
+    assert!(eval(r#"from_query_string("a=%4")"#).is_err());
+    assert!(eval(r#"from_query_string("a=%zz")"#).is_err());
+    assert_eq!(
+        eval(r#"from_query_string("a=%+1")"#),
+        Err(String::from("Invalid percent-encoding in `%+1`"))
+    );
+    assert!(eval(r#"from_query_string("a=%-1")"#).is_err());
+    assert!(eval(r#"from_query_string("a=%ff")"#).is_err());
+    assert!(eval("from_query_string(5)").is_err());
+
+    // round trips give back the same object, with every value as a string
+    assert_eq_db!(
+        r#"from_query_string(to_query_string({"to do": "a&b=c", n: 1, ok: true, emoji: "🦀"}))"#,
+        r#"{"to do": "a&b=c", n: "1", ok: "true", emoji: "🦀"}"#
+    );
 }
@@ -0,0 +1,101 @@
+use std::ops::{Add, Div, Mul, Rem, Sub};
+
+use super::prelude::*;
+
+impl Value {
+    /// Do the arithmetic for `op`, which can be `+`, `-`, `*`, `/`, or `%` (or one of their `=` versions). Here's what
+    /// each pair of types does; anything not in the table gives `undefined`:
+    ///
+    /// | left     | op  | right    | result                                                                         |
+    /// |----------|-----|----------|--------------------------------------------------------------------------------|
+    /// | number   | any | number   | the usual, but dividing by zero is `undefined`                                 |
+    /// | boolean  | `+` | number   | the number plus 0 for `false`, 0.5 for `maybe`, or 1 for `true`                |
+    /// | number   | `+` | boolean  | the same                                                                       |
+    /// | string   | `+` | anything | the right side, as it's displayed, stuck on the end                            |
+    /// | array    | `+` | array    | the two joined together                                                        |
+    /// | array    | `+` | anything | the array with the right side on the end                                       |
+    /// | anything | `+` | array    | the array with the left side at the start                                      |
+    /// | string   | `*` | number   | the string repeated, with part of it for a fraction, and backwards if negative |
+    /// | function | `*` | function | the two composed, if the left one takes one argument                           |
+    ///
+    /// Rows higher up win, so a string plus an array is a string
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    pub fn arith(op: Operation, lhs: Self, rhs: Self) -> SResult<Self> {
+        let op = match op {
+            Operation::AddEq => Operation::Add,
+            Operation::SubEq => Operation::Sub,
+            Operation::MulEq => Operation::Mul,
+            Operation::DivEq => Operation::Div,
+            Operation::ModEq => Operation::Mod,
+            other => other,
+        };
+        Ok(match (op, lhs, rhs) {
+            (Operation::Div | Operation::Mod, Self::Number(_), Self::Number(0.0)) => {
+                Self::default()
+            }
+            (Operation::Add, Self::Number(lhs), Self::Number(rhs)) => Self::Number(lhs + rhs),
+            (Operation::Sub, Self::Number(lhs), Self::Number(rhs)) => Self::Number(lhs - rhs),
+            (Operation::Mul, Self::Number(lhs), Self::Number(rhs)) => Self::Number(lhs * rhs),
+            (Operation::Div, Self::Number(lhs), Self::Number(rhs)) => Self::Number(lhs / rhs),
+            (Operation::Mod, Self::Number(lhs), Self::Number(rhs)) => Self::Number(lhs % rhs),
+            (Operation::Add, Self::Boolean(bool), Self::Number(num))
+            | (Operation::Add, Self::Number(num), Self::Boolean(bool)) => {
+                Self::Number(bool.to_number() + num)
+            }
+            (Operation::Add, Self::String(lhs), rhs) => {
+                Self::String((String::from(&*lhs) + &rhs.to_string()).into())
+            }
+            (Operation::Add, lhs @ Self::Array(_), rhs)
+            | (Operation::Add, lhs, rhs @ Self::Array(_)) => Self::coalesce_array(&lhs, &rhs),
+            (Operation::Mul, Self::String(str), Self::Number(num)) => {
+                Self::check_repeat(&str, num, None)?;
+                let mut str_buf = str.repeat(num.abs().floor() as usize);
+                let portion =
+                    ((num.abs() - num.abs().floor()) * str.chars().count() as f64) as usize;
+                str_buf.extend(str.chars().take(portion));
+                if num.is_sign_negative() {
+                    str_buf = str_buf.chars().rev().collect();
+                }
+                Self::String(str_buf.into())
+            }
+            (Operation::Mul, Self::Function(lhs_args, lhs), Self::Function(rhs_args, rhs))
+                if lhs_args.len() == 1 =>
+            {
+                Self::Function(
+                    rhs_args,
+                    Syntax::UnaryOperation(
+                        UnaryOperation::Call(vec![rhs]),
+                        Box::new(Syntax::Function(lhs_args, Box::new(lhs))),
+                    ),
+                )
+            }
+            (
+                Operation::Add | Operation::Sub | Operation::Mul | Operation::Div | Operation::Mod,
+                _,
+                _,
+            ) => Self::default(),
+            (op, _, _) => return Err(format!("`{op}` isn't an arithmetic operator")),
+        })
+    }
+}
+
+macro_rules! arith_op {
+    ($trait:ident, $func:ident, $op:ident) => {
+        impl $trait for Value {
+            type Output = Self;
+            fn $func(self, rhs: Self) -> Self::Output {
+                Self::arith(Operation::$op, self, rhs).unwrap_or_default()
+            }
+        }
+    };
+}
+
+arith_op!(Add, add, Add);
+arith_op!(Sub, sub, Sub);
+arith_op!(Mul, mul, Mul);
+arith_op!(Div, div, Div);
+arith_op!(Rem, rem, Mod);
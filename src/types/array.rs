@@ -0,0 +1,445 @@
+use std::collections::BTreeMap;
+
+use super::prelude::*;
+
+/// The most numbers `range` will make, so a typo like `range(1e18)` is an error instead of running out of memory
+const MAX_RANGE_LEN: usize = 1 << 24;
+
+impl Value {
+    /// `range(end)`, `range(start, end)`, or `range(start, end, step)`. The end is never included, and every number
+    /// has to be finite
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    pub fn range(args: &[Self]) -> SResult<Self> {
+        let (start, end, step) = match args {
+            [Self::Number(end)] => (0.0, *end, 1.0),
+            [Self::Number(start), Self::Number(end)] => (*start, *end, 1.0),
+            [Self::Number(start), Self::Number(end), Self::Number(step)] => (*start, *end, *step),
+            _ => {
+                return Err(format!(
+                    "`range` requires one to three numbers; got `{args:?}`"
+                ))
+            }
+        };
+        if step == 0.0 {
+            return Err(String::from("`range` step can't be zero"));
+        }
+        if !(start.is_finite() && end.is_finite() && step.is_finite()) {
+            return Err(format!(
+                "`range` requires finite numbers; got `{start}`, `{end}`, and `{step}`"
+            ));
+        }
+        let count = ((end - start) / step).ceil().max(0.0);
+        if count > MAX_RANGE_LEN as f64 {
+            return Err(format!(
+                "`range` can make at most {MAX_RANGE_LEN} numbers; this would be {count}"
+            ));
+        }
+        let count = count as usize;
+        Ok(Self::from(
+            (0..count)
+                .map(|idx| Self::Number((idx as f64).mul_add(step, start)))
+                .collect::<Vec<_>>(),
+        ))
+    }
+
+    /// Add up the items of an array, turning each one into a number first. An empty array adds up to 0
+    pub fn sum(&self) -> SResult<Self> {
+        Ok(Self::Number(self.numbers("sum")?.into_iter().sum()))
+    }
+
+    /// Multiply the items of an array, turning each one into a number first. An empty array multiplies to 1
+    pub fn product(&self) -> SResult<Self> {
+        Ok(Self::Number(self.numbers("product")?.into_iter().product()))
+    }
+
+    /// Get the mean of the items of an array, turning each one into a number first. Nobody can say what the average of an empty array is, so it's `maybe`
+    #[allow(clippy::cast_precision_loss)]
+    pub fn average(&self) -> SResult<Self> {
+        let nums = self.numbers("average")?;
+        if nums.is_empty() {
+            return Ok(Self::Boolean(Boolean::Maybe));
+        }
+        Ok(Self::Number(nums.iter().sum::<f64>() / nums.len() as f64))
+    }
+
+    /// Get an array of an object's keys, in the same order they're displayed in
+    pub fn keys(&self) -> SResult<Self> {
+        match self {
+            Self::Object(obj) => Ok(Self::from(obj.keys().cloned().collect::<Vec<_>>())),
+            other => Err(format!("`keys` requires an object; got `{other}`")),
+        }
+    }
+
+    /// Build an object out of an array of `[key, value]` pairs. If a key shows up more than once, the last one wins
+    pub fn to_object(&self) -> SResult<Self> {
+        #[allow(clippy::mutable_key_type)]
+        let mut obj = BTreeMap::new();
+        for pair in self.items("to_object")? {
+            let [key, value] = &pair.items("to_object")?[..] else {
+                return Err(format!(
+                    "`to_object` requires `[key, value]` pairs; got `{pair}`"
+                ));
+            };
+            obj.insert(
+                key.clone(),
+                Pointer::from(value.clone()).convert(VarType::VarVar),
+            );
+        }
+        Ok(Self::Object(obj.into()))
+    }
+
+    /// Pair up the items of two arrays, stopping at the end of the shorter one
+    pub fn zip(&self, other: &Self) -> SResult<Self> {
+        Ok(Self::from(
+            self.items("zip")?
+                .into_iter()
+                .zip(other.items("zip")?)
+                .map(|(lhs, rhs)| Self::from(vec![lhs, rhs]))
+                .collect::<Vec<_>>(),
+        ))
+    }
+
+    /// Get the first `count` items of an array, or all of them if there aren't that many. `count` has to be a whole
+    /// number that isn't negative
+    pub fn take(&self, count: &Self) -> SResult<Self> {
+        let count = to_count("take", count)?;
+        Ok(Self::from(
+            self.items("take")?
+                .into_iter()
+                .take(count)
+                .collect::<Vec<_>>(),
+        ))
+    }
+
+    /// Get everything after the first `count` items of an array, which is nothing if there aren't that many. `count`
+    /// has to be a whole number that isn't negative
+    pub fn drop(&self, count: &Self) -> SResult<Self> {
+        let count = to_count("drop", count)?;
+        Ok(Self::from(
+            self.items("drop")?
+                .into_iter()
+                .skip(count)
+                .collect::<Vec<_>>(),
+        ))
+    }
+
+    /// Split an array into arrays of `size` items each. The last one gets whatever is left over, so it might be shorter.
+    /// `size` has to be a whole number greater than 0
+    pub fn chunk(&self, size: &Self) -> SResult<Self> {
+        let size = to_count("chunk", size)?;
+        if size == 0 {
+            return Err(String::from("`chunk` size can't be zero"));
+        }
+        Ok(Self::from(
+            self.items("chunk")?
+                .chunks(size)
+                .map(|chunk| Self::from(chunk.to_vec()))
+                .collect::<Vec<_>>(),
+        ))
+    }
+
+    /// Get every run of `size` items in a row from an array, so they overlap. An array with fewer than `size` items has
+    /// none. `size` has to be a whole number greater than 0
+    pub fn windows(&self, size: &Self) -> SResult<Self> {
+        let size = to_count("windows", size)?;
+        if size == 0 {
+            return Err(String::from("`windows` size can't be zero"));
+        }
+        Ok(Self::from(
+            self.items("windows")?
+                .windows(size)
+                .map(|window| Self::from(window.to_vec()))
+                .collect::<Vec<_>>(),
+        ))
+    }
+
+    /// Make a new array with `item` put in at `index`, moving everything from there on back by one. Indices start at -1
+    /// like `.`, so -1 puts it at the start and the last index puts it at the end. Anything further out is an error
+    pub fn insert(&self, index: &Self, item: Self) -> SResult<Self> {
+        let mut items = self.items("insert")?;
+        let index = to_index("insert", index, items.len() + 1)?;
+        items.insert(index, item);
+        Ok(Self::from(items))
+    }
+
+    /// Make a new array without the item at `index`. Indices start at -1 like `.`, and it's an error if there's no item
+    /// there
+    pub fn remove_at(&self, index: &Self) -> SResult<Self> {
+        let mut items = self.items("remove_at")?;
+        let index = to_index("remove_at", index, items.len())?;
+        items.remove(index);
+        Ok(Self::from(items))
+    }
+
+    /// Pick a random item from an array, or `undefined` if it's empty. With a `count`, pick that many different items
+    /// instead (as many as there are, if there aren't enough), in the order they were picked. `count` has to be a
+    /// whole number that isn't negative
+    pub fn sample(&self, count: Option<&Self>, rng: &mut Rng) -> SResult<Self> {
+        let mut items = self.items("sample")?;
+        let Some(count) = count else {
+            if items.is_empty() {
+                return Ok(Self::default());
+            }
+            return Ok(items.swap_remove(rng.next_index(items.len())));
+        };
+        let count = to_count("sample", count)?.min(items.len());
+        shuffle_front(&mut items, count, rng);
+        items.truncate(count);
+        Ok(Self::from(items))
+    }
+
+    /// Make a copy of an array with its items in a random order
+    pub fn shuffle(&self, rng: &mut Rng) -> SResult<Self> {
+        let mut items = self.items("shuffle")?;
+        let len = items.len();
+        shuffle_front(&mut items, len, rng);
+        Ok(Self::from(items))
+    }
+
+    /// Pair each item of an array with its index. Indices start at -1, just like `.`
+    #[allow(clippy::cast_precision_loss)]
+    pub fn enumerate(&self) -> SResult<Self> {
+        Ok(Self::from(
+            self.items("enumerate")?
+                .into_iter()
+                .enumerate()
+                .map(|(idx, item)| Self::from(vec![Self::Number(idx as f64 - 1.0), item]))
+                .collect::<Vec<_>>(),
+        ))
+    }
+
+    /// Count how many times `needle` shows up in an array (using `eq` with the given precision) or a string (without
+    /// overlapping, so `aa` is in `aaaa` twice).
+    /// Without a needle, an array gives back an object mapping each different item to its count
+    #[allow(clippy::cast_precision_loss)]
+    pub fn count(&self, needle: Option<&Self>, precision: u8) -> SResult<Self> {
+        match (self, needle) {
+            (Self::String(_), Some(Self::String(needle))) if needle.is_empty() => {
+                Ok(Self::Number(0.0))
+            }
+            (Self::String(str), Some(Self::String(needle))) => {
+                Ok(Self::Number(str.matches(needle.as_ref()).count() as f64))
+            }
+            (Self::Array(arr), Some(needle)) => Ok(Self::Number(
+                arr.iter()
+                    .filter(|item| {
+                        item.with_ref(|item| item.eq(needle, precision)) == Self::from(true)
+                    })
+                    .count() as f64,
+            )),
+            (Self::Array(arr), None) => {
+                #[allow(clippy::mutable_key_type)]
+                let mut counts = BTreeMap::new();
+                for item in arr {
+                    *counts.entry(item.clone_inner()).or_insert(0.0) += 1.0;
+                }
+                Ok(Self::Object(
+                    counts
+                        .into_iter()
+                        .map(|(item, count)| (item, Pointer::from(count)))
+                        .collect(),
+                ))
+            }
+            (other, _) => Err(format!(
+                "`count` requires a string and a substring, or an array; got `{other}`"
+            )),
+        }
+    }
+
+    /// Count how many items of an array, or values of an object, are truthy, falsey, and maybeyey. The result always
+    /// has all three of `true`, `false`, and `maybe`, even when some of them are 0
+    #[allow(clippy::cast_precision_loss)]
+    pub fn truthy_count(&self) -> SResult<Self> {
+        let [truthy, falsey, maybeyey] = self.tally("truthy_count")?;
+        Ok(Self::Object(
+            [("true", truthy), ("false", falsey), ("maybe", maybeyey)]
+                .into_iter()
+                .map(|(key, count)| {
+                    (
+                        Self::from(key),
+                        Pointer::from(count as f64).convert(VarType::VarVar),
+                    )
+                })
+                .collect(),
+        ))
+    }
+
+    /// Group the items of an array into an object, using `key` to decide where each one goes.
+    /// Like every object, the groups are sorted by key, and each group keeps the order of the original array
+    pub fn group_by<F: FnMut(&Self) -> SResult<Self>>(&self, mut key: F) -> SResult<Self> {
+        #[allow(clippy::mutable_key_type)]
+        let mut groups: BTreeMap<Self, Vec<Self>> = BTreeMap::new();
+        for item in self.items("group_by")? {
+            groups.entry(key(&item)?).or_default().push(item);
+        }
+        Ok(Self::Object(
+            groups
+                .into_iter()
+                .map(|(key, group)| (key, Pointer::from(Self::from(group))))
+                .collect(),
+        ))
+    }
+
+    /// Call `func` on each item of an array and join together the arrays it gives back, in order. It has to give back
+    /// an array every time, even if it's an empty one
+    pub fn flat_map<F: FnMut(&Self) -> SResult<Self>>(&self, mut func: F) -> SResult<Self> {
+        let mut flattened = Vec::new();
+        for item in self.items("flat_map")? {
+            match func(&item)? {
+                Self::Array(arr) => flattened.extend(arr.iter().map(Pointer::clone_inner)),
+                other => {
+                    return Err(format!(
+                        "`flat_map` requires a function that gives back arrays; got `{other}`"
+                    ))
+                }
+            }
+        }
+        Ok(Self::from(flattened))
+    }
+
+    /// Sort an array by what `key` gives back for each item, working out each key only once. Items with the same key
+    /// stay in the order they were in. Keys can be anything, and are ordered the same way as object keys
+    pub fn sort_by_key<F: FnMut(&Self) -> SResult<Self>>(&self, mut key: F) -> SResult<Self> {
+        let mut keyed = self
+            .items("sort_by_key")?
+            .into_iter()
+            .map(|item| Ok((key(&item)?, item)))
+            .collect::<SResult<Vec<_>>>()?;
+        keyed.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+        Ok(Self::from(
+            keyed.into_iter().map(|(_, item)| item).collect::<Vec<_>>(),
+        ))
+    }
+
+    /// Follow an array of keys and indices through objects, arrays, and strings, the same way `.` would.
+    /// Missing anything along the way gives `undefined`
+    pub fn deep_get(&self, path: &Self) -> SResult<Self> {
+        Ok(path
+            .items("deep_get")?
+            .iter()
+            .try_fold(Pointer::from(self.clone()), |ptr, key| ptr.dot(key))?
+            .clone_inner())
+    }
+
+    /// Get the items of an array as numbers, or complain that `name` needs them
+    fn numbers(&self, name: &str) -> SResult<Vec<f64>> {
+        self.items(name)?
+            .iter()
+            .map(|item| {
+                item.to_number()
+                    .ok_or_else(|| format!("`{name}` requires numbers; got `{item}`"))
+            })
+            .collect()
+    }
+
+    /// Add two things where at least one of them is an array. Two arrays get joined together, and anything else
+    /// becomes a new item on the end (if it's on the right) or at the start (if it's on the left)
+    #[must_use]
+    pub fn coalesce_array(lhs: &Self, rhs: &Self) -> Self {
+        let as_items = |value: &Self| match value {
+            Self::Array(arr) => arr.iter().map(Pointer::clone_inner).collect(),
+            other => vec![other.clone()],
+        };
+        let mut items: Vec<Self> = as_items(lhs);
+        items.extend(as_items(rhs));
+        Self::from(items)
+    }
+
+    /// Decide what an array (or the values of an object) thinks as a whole: `true` if more things are truthy than
+    /// anything else, `false` if more are falsey, and `maybe` if more are maybeyey or there's a tie for the most. An
+    /// empty vote is a tie
+    pub fn vote(&self) -> SResult<Self> {
+        let [truthy, falsey, maybeyey] = self.tally("vote")?;
+        Ok(Self::Boolean(if truthy > falsey && truthy > maybeyey {
+            Boolean::True
+        } else if falsey > truthy && falsey > maybeyey {
+            Boolean::False
+        } else {
+            Boolean::Maybe
+        }))
+    }
+
+    /// Count the truthy, falsey, and maybeyey items of an array or values of an object, or complain that `name` needs
+    /// one of them
+    fn tally(&self, name: &str) -> SResult<[usize; 3]> {
+        let values: Vec<Self> = match self {
+            Self::Array(arr) => arr.iter().map(Pointer::clone_inner).collect(),
+            Self::Object(obj) => obj.values().map(Pointer::clone_inner).collect(),
+            other => {
+                return Err(format!(
+                    "`{name}` requires an array or an object; got `{other}`"
+                ))
+            }
+        };
+        let mut counts = [0; 3];
+        for value in values {
+            counts[match value.bool() {
+                Boolean::True => 0,
+                Boolean::False => 1,
+                Boolean::Maybe => 2,
+            }] += 1;
+        }
+        Ok(counts)
+    }
+
+    /// Get the values in an array, or complain that `name` needs one
+    fn items(&self, name: &str) -> SResult<Vec<Self>> {
+        match self {
+            Self::Array(arr) => Ok(arr.iter().map(Pointer::clone_inner).collect()),
+            other => Err(format!("`{name}` requires an array; got `{other}`")),
+        }
+    }
+}
+
+/// Turn a number of items into a `usize`, or complain that `name` needs a whole number that isn't negative
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn to_count(name: &str, count: &Value) -> SResult<usize> {
+    match count {
+        Value::Number(num) if num.fract() == 0.0 && *num >= 0.0 => Ok(*num as usize),
+        other => Err(format!(
+            "`{name}` requires a whole number that isn't negative; got `{other}`"
+        )),
+    }
+}
+
+/// Fill the first `count` places of `items` with random picks from all of them, using the first part of a Fisher-Yates
+/// shuffle. Shuffling the whole thing just means picking all of them
+fn shuffle_front(items: &mut [Value], count: usize, rng: &mut Rng) {
+    for idx in 0..count {
+        let pick = idx + rng.next_index(items.len() - idx);
+        items.swap(idx, pick);
+    }
+}
+
+/// Turn an index that starts at -1 into one that starts at 0, or complain that `name` needs a whole number that points
+/// at one of the `len` places it could go
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn to_index(name: &str, index: &Value, len: usize) -> SResult<usize> {
+    match index {
+        Value::Number(num) if num.fract() == 0.0 && *num >= -1.0 && *num < len as f64 - 1.0 => {
+            Ok((*num + 1.0) as usize)
+        }
+        other => Err(format!(
+            "`{name}` requires a whole number index that fits in the array; got `{other}`"
+        )),
+    }
+}
+
+impl From<Vec<Self>> for Value {
+    fn from(value: Vec<Self>) -> Self {
+        Self::Array(
+            value
+                .into_iter()
+                .map(|item| Pointer::from(item).convert(VarType::VarVar))
+                .collect(),
+        )
+    }
+}
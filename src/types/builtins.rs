@@ -0,0 +1,26 @@
+use std::{collections::BTreeMap, rc::Rc};
+
+use super::prelude::*;
+
+/// A builtin written in Rust. It gets its arguments already evaluated
+pub type Builtin = fn(&[Value]) -> SResult<Value>;
+
+/// Functions that the host program makes available to scripts by name. Names that are already taken by a keyword or a
+/// variable in scope shadow them
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Builtins(BTreeMap<Rc<str>, Builtin>);
+
+impl Builtins {
+    /// Make `func` callable as `name`, replacing anything that was registered under that name before
+    pub fn register(&mut self, name: &str, func: Builtin) {
+        self.0.insert(name.into(), func);
+    }
+
+    pub fn get(&self, name: &str) -> Option<Builtin> {
+        self.0.get(name).copied()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.contains_key(name)
+    }
+}
@@ -0,0 +1,62 @@
+/// Settings shared by every scope of a running program
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)] // they're independent switches, not a state machine
+pub struct Config {
+    /// The precision used by equality checks that aren't written with `=`s, like `equals(a, b)`
+    pub default_precision: u8,
+    /// Reject characters that don't belong in identifiers instead of lexing them into one
+    pub strict_lexing: bool,
+    /// Keep comments as `Token::Comment`s instead of throwing them away
+    pub preserve_comments: bool,
+    /// The longest a string can be, either as a literal or after being multiplied
+    pub max_string_length: Option<usize>,
+    /// The most tokens a program can be lexed into, counting the `!`s that `newline_statements` adds
+    pub max_tokens: Option<usize>,
+    /// Make writing to a frozen value an error instead of doing nothing
+    pub strict_freezing: bool,
+    /// Make a maybeyey condition in `if`, `assert`, or a comprehension an error instead of a third option
+    pub strict_conditions: bool,
+    /// Make dividing by zero with `/` or `%` an error instead of `undefined`
+    pub strict_division: bool,
+    /// Make object literals remember the order their keys were written in, instead of sorting them
+    pub preserve_object_order: bool,
+    /// Make using anything other than a string, number, boolean, or keyword as an object key an error
+    pub strict_keys: bool,
+    /// Let a newline end a statement, like a `!`, when the line looks finished
+    pub newline_statements: bool,
+    /// How far apart two numbers can be and still be equal with one `=`, measured as the log of their ratio. Smaller
+    /// numbers are pickier
+    pub fuzzy_tolerance: Tolerance,
+}
+
+/// A `fuzzy_tolerance`. Two of them are only equal if they're exactly the same number, so even a `NaN` tolerance is
+/// equal to itself, and settings can be `Eq`
+#[derive(Debug, Clone, Copy)]
+pub struct Tolerance(pub f64);
+
+impl PartialEq for Tolerance {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+
+impl Eq for Tolerance {}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            default_precision: 2,
+            strict_lexing: false,
+            preserve_comments: false,
+            max_string_length: None,
+            max_tokens: None,
+            strict_freezing: false,
+            strict_conditions: false,
+            strict_division: false,
+            preserve_object_order: false,
+            strict_keys: false,
+            newline_statements: false,
+            fuzzy_tolerance: Tolerance(0.1),
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use std::collections::BTreeSet;
+
+use super::prelude::*;
+
+impl Value {
+    /// Write an array of objects as CSV. The header is every key that shows up in any of the objects, sorted, and
+    /// objects that don't have one of the keys leave that field empty. Fields with commas, quotes, or newlines are quoted
+    pub fn to_csv(&self) -> SResult<Self> {
+        let rows = self
+            .as_array()
+            .ok_or_else(|| format!("`to_csv` requires an array of objects; got `{self}`"))?
+            .iter()
+            .map(|row| match row.clone_inner() {
+                Self::Object(obj) => Ok(obj),
+                other => Err(format!("`to_csv` rows must be objects; got `{other}`")),
+            })
+            .collect::<SResult<Vec<_>>>()?;
+        if rows.is_empty() {
+            return Ok(Self::from(""));
+        }
+        #[allow(clippy::mutable_key_type)]
+        let header = rows
+            .iter()
+            .flat_map(|row| row.keys().cloned())
+            .collect::<BTreeSet<_>>();
+        let mut lines = vec![header.iter().map(csv_field).collect::<Vec<_>>().join(",")];
+        for row in &rows {
+            let fields = header.iter().map(|key| {
+                row.get(key)
+                    .map(|value| csv_field(&value.clone_inner()))
+                    .unwrap_or_default()
+            });
+            lines.push(fields.collect::<Vec<_>>().join(","));
+        }
+        Ok(Self::from(lines.join("\n").as_str()))
+    }
+
+    /// Read CSV into an array of objects, using the first row as the keys. Every field is a string, since CSV doesn't
+    /// say what's a number. Lines can end with `\n` or `\r\n`, and a newline at the very end is ignored
+    pub fn parse_csv(&self) -> SResult<Self> {
+        let Self::String(text) = self else {
+            return Err(format!("`from_csv` requires a string; got `{self}`"));
+        };
+        let mut records = split_records(text)?.into_iter();
+        let Some(header) = records.next() else {
+            return Ok(Self::from(Vec::new()));
+        };
+        let rows = records
+            .enumerate()
+            .map(|(idx, record)| {
+                if record.len() != header.len() {
+                    return Err(format!(
+                        "CSV row {} has {} fields, but the header has {}",
+                        idx + 1,
+                        record.len(),
+                        header.len()
+                    ));
+                }
+                Ok(Self::Object(
+                    header
+                        .iter()
+                        .zip(record)
+                        .map(|(key, field)| {
+                            (
+                                Self::from(key.as_str()),
+                                Pointer::from(Self::from(field.as_str())).convert(VarType::VarVar),
+                            )
+                        })
+                        .collect(),
+                ))
+            })
+            .collect::<SResult<Vec<_>>>()?;
+        Ok(Self::from(rows))
+    }
+}
+
+fn csv_field(value: &Value) -> String {
+    let text = value.to_string();
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+/// Split CSV text into records of fields. Quoted fields can have commas and newlines in them, and `""` is a quote
+fn split_records(text: &str) -> SResult<Vec<Vec<String>>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if field.is_empty() => loop {
+                match chars.next() {
+                    Some('"') if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        field.push('"');
+                    }
+                    Some('"') => break,
+                    Some(ch) => field.push(ch),
+                    None => return Err(String::from("Unterminated quoted CSV field")),
+                }
+            },
+            ',' => record.push(core::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(core::mem::take(&mut field));
+                records.push(core::mem::take(&mut record));
+            }
+            ch => field.push(ch),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
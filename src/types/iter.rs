@@ -0,0 +1,49 @@
+use std::{iter::Enumerate, slice, str::Chars};
+
+use super::prelude::*;
+
+/// Iterator over the `(key, value)` pairs of a [`Value`]. Arrays and strings are keyed by position, and anything that
+/// isn't a container is empty
+pub enum Iter<'a> {
+    Object(super::object_map::Iter<'a>),
+    Array(Enumerate<slice::Iter<'a, Pointer>>),
+    String(Enumerate<Chars<'a>>),
+    Empty,
+}
+
+impl Iterator for Iter<'_> {
+    type Item = (Value, Value);
+
+    #[allow(clippy::cast_precision_loss)]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Object(entries) => entries
+                .next()
+                .map(|(key, value)| (key.clone(), value.clone_inner())),
+            Self::Array(items) => items
+                .next()
+                .map(|(idx, item)| (Value::Number(idx as f64), item.clone_inner())),
+            Self::String(chars) => chars.next().map(|(idx, ch)| {
+                (
+                    Value::Number(idx as f64),
+                    Value::from(ch.to_string().as_str()),
+                )
+            }),
+            Self::Empty => None,
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Value {
+    type Item = (Value, Value);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        match self {
+            Value::Object(obj) => Iter::Object(obj.iter()),
+            Value::Array(arr) => Iter::Array(arr.iter().enumerate()),
+            Value::String(str) => Iter::String(str.chars().enumerate()),
+            _ => Iter::Empty,
+        }
+    }
+}
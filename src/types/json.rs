@@ -0,0 +1,72 @@
+use serde_json::{Map, Number};
+
+use super::prelude::*;
+
+/// `null` is the closest JSON gets to `maybe`
+impl From<serde_json::Value> for Value {
+    fn from(value: serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => Self::Boolean(Boolean::Maybe),
+            serde_json::Value::Bool(bool) => Self::from(bool),
+            serde_json::Value::Number(num) => Self::Number(num.as_f64().unwrap_or(f64::NAN)),
+            serde_json::Value::String(str) => Self::from(str.as_str()),
+            serde_json::Value::Array(arr) => {
+                Self::from(arr.into_iter().map(Self::from).collect::<Vec<_>>())
+            }
+            serde_json::Value::Object(obj) => Self::Object(
+                obj.into_iter()
+                    .map(|(key, value)| {
+                        (
+                            Self::from(key.as_str()),
+                            Pointer::from(Self::from(value)).convert(VarType::VarVar),
+                        )
+                    })
+                    .collect::<ObjectMap>(),
+            ),
+        }
+    }
+}
+
+/// Functions, classes, keywords, and numbers that JSON can't write down, like `NaN`, don't convert. Keys are
+/// converted to strings. Objects that contain themselves never finish converting
+impl TryFrom<&Value> for serde_json::Value {
+    type Error = String;
+
+    fn try_from(value: &Value) -> SResult<Self> {
+        Ok(match value {
+            Value::Boolean(Boolean::Maybe) => Self::Null,
+            Value::Boolean(Boolean::True) => Self::Bool(true),
+            Value::Boolean(Boolean::False) => Self::Bool(false),
+            Value::String(str) => Self::String(str.to_string()),
+            // whole numbers are written without a `.0`, as long as they fit exactly
+            #[allow(clippy::cast_possible_truncation)]
+            Value::Number(num) if num.fract() == 0.0 && num.abs() < 2f64.powi(53) => {
+                Self::Number(Number::from(*num as i64))
+            }
+            Value::Number(num) => Number::from_f64(*num)
+                .map(Self::Number)
+                .ok_or_else(|| format!("JSON can't represent the number `{num}`"))?,
+            Value::Array(arr) => Self::Array(
+                arr.iter()
+                    .map(|item| Self::try_from(&item.clone_inner()))
+                    .collect::<SResult<_>>()?,
+            ),
+            Value::Object(obj) => Self::Object(
+                obj.iter()
+                    .map(|(key, value)| {
+                        Ok((key.to_string(), Self::try_from(&value.clone_inner())?))
+                    })
+                    .collect::<SResult<Map<_, _>>>()?,
+            ),
+            other => return Err(format!("JSON can't represent `{other}`")),
+        })
+    }
+}
+
+impl TryFrom<Value> for serde_json::Value {
+    type Error = String;
+
+    fn try_from(value: Value) -> SResult<Self> {
+        Self::try_from(&value)
+    }
+}
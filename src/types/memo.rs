@@ -0,0 +1,50 @@
+use std::{cell::RefCell, collections::HashMap, fmt, hash::Hash, ptr};
+
+use super::prelude::*;
+
+/// A function made by `memoize`, along with every result it has worked out. The cache belongs to this one wrapper:
+/// copies of it share the cache, but memoizing the same function again starts over
+pub struct Memo {
+    func: Value,
+    cache: RefCell<HashMap<Vec<Value>, Value>>,
+}
+
+impl Memo {
+    pub fn new(func: Value) -> Self {
+        Self {
+            func,
+            cache: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub const fn func(&self) -> &Value {
+        &self.func
+    }
+
+    pub fn get(&self, args: &[Value]) -> Option<Value> {
+        self.cache.borrow().get(args).cloned()
+    }
+
+    pub fn insert(&self, args: Vec<Value>, result: Value) {
+        self.cache.borrow_mut().insert(args, result);
+    }
+}
+
+/// Wrappers are only equal to themselves, since two with the same function can have different caches
+impl PartialEq for Memo {
+    fn eq(&self, other: &Self) -> bool {
+        ptr::eq(self, other)
+    }
+}
+
+impl Hash for Memo {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        ptr::from_ref(self).hash(state);
+    }
+}
+
+impl fmt::Display for Memo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "memoized {}", self.func)
+    }
+}
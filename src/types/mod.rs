@@ -1,16 +1,37 @@
 pub use prelude::*;
 
+mod arith;
+mod array;
+mod builtins;
+mod config;
+mod csv;
+mod iter;
+#[cfg(feature = "serde_json")]
+mod json;
+mod memo;
+mod number;
+mod object;
+mod object_map;
 mod pointer;
+mod query;
+mod random;
 mod state;
+mod string;
 mod syntax;
 mod token;
 mod value;
+mod walk;
 
 pub mod prelude {
     use std::cell::RefCell;
     use std::rc::Rc;
 
+    pub use super::builtins::Builtins;
+    pub use super::config::{Config, Tolerance};
+    pub use super::memo::Memo;
+    pub use super::object_map::ObjectMap;
     pub use super::pointer::{MutValue, Pointer};
+    pub use super::random::Rng;
     pub use super::state::State;
     pub use super::syntax::{Lifetime, Operation, Syntax, UnaryOperation, VarType};
     pub use super::token::{StringSegment, Token};
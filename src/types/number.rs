@@ -0,0 +1,113 @@
+use super::prelude::*;
+
+impl Value {
+    /// Keep a number between `lo` and `hi`. The bounds can come in either order, `NaN` stays `NaN`,
+    /// and anything that isn't a number is left alone
+    pub fn clamp(&self, lo: &Self, hi: &Self) -> SResult<Self> {
+        let (Some(lo), Some(hi)) = (lo.to_number(), hi.to_number()) else {
+            return Err(format!(
+                "`clamp` bounds must be numbers; got `{lo}` and `{hi}`"
+            ));
+        };
+        match self {
+            Self::Number(num) if num.is_nan() => Ok(self.clone()),
+            Self::Number(num) => Ok(Self::Number(num.max(lo.min(hi)).min(lo.max(hi)))),
+            other => Ok(other.clone()),
+        }
+    }
+
+    /// Check if this is a number that isn't infinite or `NaN`. Anything that isn't a number is `maybe` finite
+    pub fn is_finite(&self) -> Self {
+        match self {
+            Self::Number(num) => Self::from(num.is_finite()),
+            _ => Self::Boolean(Boolean::Maybe),
+        }
+    }
+
+    /// Check if this is the number `NaN`. Anything that isn't a number is `maybe` not a number
+    pub fn is_nan(&self) -> Self {
+        match self {
+            Self::Number(num) => Self::from(num.is_nan()),
+            _ => Self::Boolean(Boolean::Maybe),
+        }
+    }
+
+    /// Run one of the math builtins on this number. Going out of a function's domain doesn't throw; you get what
+    /// `f64` gives you, like `NaN` for `sqrt(;1)` or negative infinity for `ln(0)`. Things that can't be numbers are
+    /// `maybe` numbers
+    pub fn math(&self, function: Keyword) -> Self {
+        let Some(num) = self.to_number() else {
+            return Self::Boolean(Boolean::Maybe);
+        };
+        Self::Number(match function {
+            Keyword::Cos => num.cos(),
+            Keyword::Exp => num.exp(),
+            Keyword::Ln => num.ln(),
+            Keyword::Log => num.log10(),
+            Keyword::Sin => num.sin(),
+            Keyword::Sqrt => num.sqrt(),
+            Keyword::Tan => num.tan(),
+            other => unreachable!("`{other}` isn't a math function"),
+        })
+    }
+
+    /// `-1`, `0`, or `1` depending on which side of zero this is. Negative zero is just zero, and anything that
+    /// can't be a number is `NaN`
+    pub fn sign(&self) -> Self {
+        match self.to_number() {
+            // `-0.0` matches too, and would otherwise have a sign of `-1`
+            Some(0.0) => Self::Number(0.0),
+            // `NaN` has a sign of `NaN`
+            Some(num) => Self::Number(num.signum()),
+            None => Self::Number(f64::NAN),
+        }
+    }
+
+    /// Write a whole number in base `radix`, using letters after `9`, like `to_base(255, 16)` is `"ff"`
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn to_base(&self, radix: &Self) -> SResult<Self> {
+        let radix = to_radix(radix)?;
+        let num = match self {
+            Self::Number(num) if num.fract() == 0.0 && num.abs() < 2f64.powi(53) => *num as i64,
+            other => return Err(format!("`to_base` requires a whole number; got `{other}`")),
+        };
+        let mut magnitude = num.unsigned_abs();
+        let mut digits = Vec::new();
+        loop {
+            let digit = (magnitude % u64::from(radix)) as u32;
+            digits.push(char::from_digit(digit, radix).unwrap_or('?'));
+            magnitude /= u64::from(radix);
+            if magnitude == 0 {
+                break;
+            }
+        }
+        if num < 0 {
+            digits.push('-');
+        }
+        Ok(Self::from(
+            digits.into_iter().rev().collect::<String>().as_str(),
+        ))
+    }
+
+    /// Read a string of base-`radix` digits back into a number. Letters can be either case, and a leading `-` makes
+    /// it negative
+    #[allow(clippy::cast_precision_loss)]
+    pub fn parse_base(&self, radix: &Self) -> SResult<Self> {
+        let radix = to_radix(radix)?;
+        let Self::String(str) = self else {
+            return Err(format!("`from_base` requires a string; got `{self}`"));
+        };
+        i64::from_str_radix(str, radix)
+            .map(|num| Self::Number(num as f64))
+            .map_err(|_| format!("`{str}` isn't a number in base {radix}"))
+    }
+}
+
+/// Bases go from 2 up to 36, when we run out of letters
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn to_radix(radix: &Value) -> SResult<u32> {
+    match radix {
+        Value::Number(num) if num.fract() == 0.0 && (2.0..=36.0).contains(num) => Ok(*num as u32),
+        other => Err(format!("`{other}` isn't a base from 2 to 36")),
+    }
+}
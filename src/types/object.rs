@@ -0,0 +1,192 @@
+use std::collections::BTreeMap;
+
+use super::{pointer::Visit, prelude::*};
+
+impl Value {
+    /// Check if this is a string, number, boolean, or keyword, which are the keys allowed with `strict_keys`. Objects
+    /// and functions can be keys otherwise, but they're much harder to find again
+    #[must_use]
+    pub const fn is_scalar_key(&self) -> bool {
+        matches!(
+            self,
+            Self::String(_) | Self::Number(_) | Self::Boolean(_) | Self::Keyword(_)
+        )
+    }
+
+    /// Compare two objects, using `eq` with the given precision to spot changes. The result can have three sections:
+    /// `added` and `removed` map keys to the values that were added or removed, and `changed` maps keys to `[old, new]`
+    /// pairs. When both sides of a change are objects, `changed` has a nested diff instead. Empty sections are left out,
+    /// so identical objects have an empty diff
+    pub fn diff(&self, other: &Self, precision: u8) -> SResult<Self> {
+        let (Self::Object(old), Self::Object(new)) = (self, other) else {
+            return Err(format!(
+                "`diff` requires two objects; got `{self}` and `{other}`"
+            ));
+        };
+        #[allow(clippy::mutable_key_type)]
+        let mut added = BTreeMap::new();
+        #[allow(clippy::mutable_key_type)]
+        let mut removed = BTreeMap::new();
+        #[allow(clippy::mutable_key_type)]
+        let mut changed = BTreeMap::new();
+        for (key, old_val) in old {
+            let old_val = old_val.clone_inner();
+            let Some(new_val) = new.get(key).map(Pointer::clone_inner) else {
+                removed.insert(key.clone(), Pointer::from(old_val));
+                continue;
+            };
+            if old_val.eq(&new_val, precision) == Self::from(true) {
+                continue;
+            }
+            let change = match (&old_val, &new_val) {
+                (Self::Object(_), Self::Object(_)) => old_val.diff(&new_val, precision)?,
+                _ => Self::from(vec![old_val, new_val]),
+            };
+            changed.insert(key.clone(), Pointer::from(change));
+        }
+        for (key, new_val) in new {
+            if !old.contains_key(key) {
+                added.insert(key.clone(), Pointer::from(new_val.clone_inner()));
+            }
+        }
+        Ok(Self::Object(
+            [("added", added), ("removed", removed), ("changed", changed)]
+                .into_iter()
+                .filter(|(_, section)| !section.is_empty())
+                .map(|(name, section)| {
+                    (
+                        Self::from(name),
+                        Pointer::from(Self::Object(section.into())),
+                    )
+                })
+                .collect(),
+        ))
+    }
+
+    /// Make a copy of an object with `key` set to `value`, without touching the original. The copy gets its own
+    /// pointers, so changing it later (even if the original is frozen) doesn't change the original either
+    pub fn with_key(&self, key: &Self, value: &Self) -> SResult<Self> {
+        let Self::Object(obj) = self else {
+            return Err(format!("`with_key` requires an object; got `{self}`"));
+        };
+        let mut obj = detach(obj);
+        obj.insert(
+            key.clone(),
+            Pointer::from(value.clone()).convert(VarType::VarVar),
+        );
+        Ok(Self::Object(obj))
+    }
+
+    /// Make a copy of an object without `key`, or without any of the keys if it's an array, and leave the original
+    /// alone. Keys that aren't there are skipped
+    pub fn without_key(&self, key: &Self) -> SResult<Self> {
+        let Self::Object(obj) = self else {
+            return Err(format!("`without_key` requires an object; got `{self}`"));
+        };
+        let mut obj = detach(obj);
+        match key.as_array() {
+            Some(keys) => keys.iter().for_each(|key| {
+                obj.remove(&key.clone_inner());
+            }),
+            None => {
+                obj.remove(key);
+            }
+        }
+        Ok(Self::Object(obj))
+    }
+
+    /// Make a copy of an object with only the keys in the array `keys`. Keys the object doesn't have are left out
+    pub fn pick(&self, keys: &Self) -> SResult<Self> {
+        let Self::Object(obj) = self else {
+            return Err(format!("`pick` requires an object; got `{self}`"));
+        };
+        let keys = keys
+            .as_array()
+            .ok_or_else(|| format!("`pick` requires an array of keys; got `{keys}`"))?
+            .iter()
+            .map(Pointer::clone_inner)
+            .collect::<Vec<_>>();
+        let mut obj = detach(obj);
+        let unpicked = obj
+            .keys()
+            .filter(|key| !keys.contains(key))
+            .cloned()
+            .collect::<Vec<_>>();
+        for key in &unpicked {
+            obj.remove(key);
+        }
+        Ok(Self::Object(obj))
+    }
+
+    /// Make a copy of an object without any of the keys in the array `keys`. This is `without_key`, but it has to be
+    /// an array
+    pub fn omit(&self, keys: &Self) -> SResult<Self> {
+        if keys.as_array().is_none() {
+            return Err(format!("`omit` requires an array of keys; got `{keys}`"));
+        }
+        self.without_key(keys)
+    }
+
+    /// Flip every boolean in an object, including in objects inside of it. `maybe` stays `maybe`, and everything else
+    /// is left alone. An object inside of itself is only inverted the first time it comes up
+    pub fn invert(&self) -> SResult<Self> {
+        let Self::Object(obj) = self else {
+            return Err(format!("`invert` requires an object; got `{self}`"));
+        };
+        let mut obj = obj.clone();
+        for (_, value) in obj.iter_mut() {
+            let inverted = value.visit(Visit::Invert, None, || match value.clone_inner() {
+                bool @ Self::Boolean(_) => Ok(-bool),
+                inner @ Self::Object(_) => inner.invert(),
+                other => Ok(other),
+            });
+            let Some(inverted) = inverted.transpose()? else {
+                continue;
+            };
+            *value = Pointer::from(inverted).convert(VarType::VarVar);
+        }
+        Ok(Self::Object(obj))
+    }
+
+    /// Fill in the keys an object doesn't have from `defaults`, without touching either of them. Keys the object
+    /// already has always win, but when both sides are objects the defaults are filled in inside of them too. Objects
+    /// inside of themselves only get their defaults the first time they come up
+    pub fn apply_defaults(&self, defaults: &Self) -> SResult<Self> {
+        let (Self::Object(original), Self::Object(defaults)) = (self, defaults) else {
+            return Err(format!(
+                "`apply_defaults` requires two objects; got `{self}` and `{defaults}`"
+            ));
+        };
+        let mut obj = detach(original);
+        for (key, default) in defaults {
+            // `None` on either level means the object's own value stays
+            let filled = original.get(key).map_or_else(
+                || Some(Ok(Some(default.clone_inner()))),
+                |value| {
+                    value.visit(Visit::Defaults, Some(default), || {
+                        match (value.clone_inner(), default.clone_inner()) {
+                            (value @ Self::Object(_), default @ Self::Object(_)) => {
+                                value.apply_defaults(&default).map(Some)
+                            }
+                            _ => Ok(None),
+                        }
+                    })
+                },
+            );
+            let Some(filled) = filled.transpose()?.flatten() else {
+                continue;
+            };
+            obj.insert(key.clone(), Pointer::from(filled).convert(VarType::VarVar));
+        }
+        Ok(Self::Object(obj))
+    }
+}
+
+/// Copy an object, giving every entry a new pointer so that nothing written to the copy shows up in the original
+fn detach(obj: &ObjectMap) -> ObjectMap {
+    let mut obj = obj.clone();
+    for (_, value) in obj.iter_mut() {
+        *value = Pointer::from(value.clone_inner()).convert(VarType::VarVar);
+    }
+    obj
+}
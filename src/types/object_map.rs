@@ -0,0 +1,182 @@
+use std::{
+    cmp::Ordering,
+    collections::{btree_map, BTreeMap},
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+    ops::Deref,
+    slice,
+};
+
+use super::prelude::*;
+
+/// The entries of an object. Normally they're kept in key order, but an ordered map remembers the order keys were
+/// first inserted in and iterates in that order instead. Either way, two maps with the same entries are equal
+#[derive(Clone, Default)]
+pub struct ObjectMap {
+    entries: BTreeMap<Value, Pointer>,
+    /// Every key in `entries`, in the order they were inserted, if this map keeps track of that
+    order: Option<Vec<Value>>,
+}
+
+impl ObjectMap {
+    pub const fn new() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+            order: None,
+        }
+    }
+
+    /// An empty map that iterates in insertion order
+    pub const fn ordered() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+            order: Some(Vec::new()),
+        }
+    }
+
+    pub const fn is_ordered(&self) -> bool {
+        self.order.is_some()
+    }
+
+    /// Insert a value, returning the one it replaced. Replacing a value keeps its key where it was
+    pub fn insert(&mut self, key: Value, value: Pointer) -> Option<Pointer> {
+        if let Some(order) = &mut self.order {
+            if !self.entries.contains_key(&key) {
+                order.push(key.clone());
+            }
+        }
+        self.entries.insert(key, value)
+    }
+
+    pub fn remove(&mut self, key: &Value) -> Option<Pointer> {
+        let removed = self.entries.remove(key)?;
+        if let Some(order) = &mut self.order {
+            order.retain(|k| k != key);
+        }
+        Some(removed)
+    }
+
+    pub fn get_mut(&mut self, key: &Value) -> Option<&mut Pointer> {
+        self.entries.get_mut(key)
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        self.order.as_ref().map_or_else(
+            || Iter::Sorted(self.entries.iter()),
+            |order| Iter::Ordered(order.iter(), &self.entries),
+        )
+    }
+
+    /// Mutable access to every entry. This is always in key order
+    pub fn iter_mut(&mut self) -> btree_map::IterMut<'_, Value, Pointer> {
+        self.entries.iter_mut()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &Value> {
+        self.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &Pointer> {
+        self.iter().map(|(_, value)| value)
+    }
+}
+
+/// Everything that only reads from the map and doesn't care about order, like `get` and `len`
+impl Deref for ObjectMap {
+    type Target = BTreeMap<Value, Pointer>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.entries
+    }
+}
+
+impl From<BTreeMap<Value, Pointer>> for ObjectMap {
+    fn from(entries: BTreeMap<Value, Pointer>) -> Self {
+        Self {
+            entries,
+            order: None,
+        }
+    }
+}
+
+impl FromIterator<(Value, Pointer)> for ObjectMap {
+    fn from_iter<T: IntoIterator<Item = (Value, Pointer)>>(iter: T) -> Self {
+        Self::from(iter.into_iter().collect::<BTreeMap<_, _>>())
+    }
+}
+
+pub enum Iter<'a> {
+    Sorted(btree_map::Iter<'a, Value, Pointer>),
+    Ordered(slice::Iter<'a, Value>, &'a BTreeMap<Value, Pointer>),
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a Value, &'a Pointer);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Sorted(entries) => entries.next(),
+            Self::Ordered(keys, entries) => keys.next().map(|key| (key, &entries[key])),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a ObjectMap {
+    type Item = (&'a Value, &'a Pointer);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+impl IntoIterator for ObjectMap {
+    type Item = (Value, Pointer);
+    type IntoIter = std::vec::IntoIter<(Value, Pointer)>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        match self.order {
+            Some(order) => order
+                .into_iter()
+                .filter_map(|key| self.entries.remove_entry(&key))
+                .collect::<Vec<_>>()
+                .into_iter(),
+            None => self.entries.into_iter().collect::<Vec<_>>().into_iter(),
+        }
+    }
+}
+
+impl PartialEq for ObjectMap {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+impl Eq for ObjectMap {}
+
+/// Maps compare their entries in key order, whichever order they iterate in. The first key or value that's different
+/// decides it, so `{a: 1, b: 5}` comes before `{a: 2}`, and `{a: 1}` comes before `{b: 0}`. A map that runs out of
+/// entries first comes first
+impl PartialOrd for ObjectMap {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ObjectMap {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.entries.cmp(&other.entries)
+    }
+}
+
+impl Hash for ObjectMap {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.entries.hash(state);
+    }
+}
+
+impl Debug for ObjectMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
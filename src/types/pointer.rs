@@ -1,6 +1,7 @@
 use core::hash::Hash;
+use std::cell::RefCell;
 use std::cmp::Ordering;
-use std::fmt::{Debug, Display};
+use std::fmt::{Debug, Display, Write};
 use std::ops::{AddAssign, BitAnd, BitOr, DivAssign, MulAssign, Neg, Rem, RemAssign, SubAssign};
 use std::{
     ops::{Add, Div, Mul, Sub},
@@ -11,7 +12,7 @@ use super::prelude::*;
 
 /// A pointer to a reference-counted value
 /// A `const const` and `var const` can point to the same value, as can a `const var` and `var var`.
-#[derive(PartialEq, Eq, Clone)]
+#[derive(Clone)]
 pub enum Pointer {
     ConstConst(Rc<Value>),
     ConstVar(RcMut<MutValue>),
@@ -24,6 +25,8 @@ pub struct MutValue {
     pub value: Value,
     pub previous: Option<Value>,
     pub event_listeners: Vec<(Syntax, RcMut<State>)>,
+    /// Frozen values can't be written to. See `Pointer::freeze`
+    pub frozen: bool,
     next_handles: Vec<Pointer>,
 }
 
@@ -51,46 +54,107 @@ impl From<Value> for MutValue {
             value,
             previous: None,
             event_listeners: Vec::new(),
+            frozen: false,
             next_handles: Vec::new(),
         }
     }
 }
 
+/// Something that walks into the values behind pointers, which could go on forever if a value contains itself
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum Visit {
+    Display,
+    Hash,
+    Eq,
+    Cmp,
+    Invert,
+    Defaults,
+}
+
+thread_local! {
+    /// The `var` values that are being visited right now, along with the value they're being compared to
+    static VISITING: RefCell<Vec<(Visit, usize, usize)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Removes a visit from `VISITING` when it's done, even if it panics
+struct Visiting;
+
+impl Drop for Visiting {
+    fn drop(&mut self) {
+        VISITING.with_borrow_mut(Vec::pop);
+    }
+}
+
 impl Debug for Pointer {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::ConstConst(val) => write!(f, "const const ({val:?})"),
-            Self::ConstVar(val) => write!(f, "const var ({:?})", val.borrow().value),
-            Self::VarConst(val) => write!(f, "var const ({:?})", val.borrow()),
-            Self::VarVar(val) => write!(f, "var var ({:?})", val.borrow().borrow().value),
-        }
+        let name = match self {
+            Self::ConstConst(_) => "const const",
+            Self::ConstVar(_) => "const var",
+            Self::VarConst(_) => "var const",
+            Self::VarVar(_) => "var var",
+        };
+        write!(f, "{name} (")?;
+        self.fmt_inner(f)?;
+        write!(f, ")")
+    }
+}
+
+/// Formats a pointer the way `debug_string` shows it
+struct StorageDebug<'a>(&'a Pointer);
+
+impl Debug for StorageDebug<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.write_debug(f)
     }
 }
 
 impl Display for Pointer {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::ConstConst(val) => write!(f, "{val}"),
-            Self::VarConst(val) => write!(f, "{}", val.borrow()),
-            Self::ConstVar(val) => write!(f, "{}", val.borrow().value),
-            Self::VarVar(val) => write!(f, "{}", val.borrow().borrow().value),
-        }
+        self.visit(Visit::Display, None, || {
+            self.with_ref(|val| write!(f, "{val}"))
+        })
+        .unwrap_or_else(|| write!(f, "<cycle>"))
     }
 }
 
 impl Hash for Pointer {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        core::mem::discriminant(self).hash(state);
-        match self {
-            Self::ConstConst(val) => val.hash(state),
-            Self::VarConst(val) => val.borrow().hash(state),
-            Self::ConstVar(var) => var.borrow().value.hash(state),
-            Self::VarVar(var) => var.borrow().borrow().value.hash(state),
-        }
+        // the rest of a value that contains itself was already hashed
+        self.visit(Visit::Hash, None, || self.with_ref(|val| val.hash(state)));
     }
 }
 
 impl Pointer {
+    /// Run `func`, unless this pointer (compared to `other`, if there is one) is already being visited the same way
+    /// further up the stack. In that case, the value contains itself, and this is `None`
+    pub fn visit<T>(
+        &self,
+        visit: Visit,
+        other: Option<&Self>,
+        func: impl FnOnce() -> T,
+    ) -> Option<T> {
+        let address = |ptr: &Self| ptr.as_var().map_or(0, |var| Rc::as_ptr(&var) as usize);
+        let key = (visit, address(self), other.map_or(0, address));
+        // constant values can't contain themselves
+        if key.1 == 0 && key.2 == 0 {
+            return Some(func());
+        }
+        if VISITING.with_borrow(|visiting| visiting.contains(&key)) {
+            return None;
+        }
+        VISITING.with_borrow_mut(|visiting| visiting.push(key));
+        let _visiting = Visiting;
+        Some(func())
+    }
+
+    /// Write the value inside the pointer like `Debug` would, or `<cycle>` if it's already being written
+    pub fn fmt_inner(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.visit(Visit::Display, None, || {
+            self.with_ref(|val| write!(f, "{val:?}"))
+        })
+        .unwrap_or_else(|| write!(f, "<cycle>"))
+    }
+
     /// Get the value inside the pointer. This is not a deep clone and should be treated as a reference
     pub fn clone_inner(&self) -> Value {
         match self {
@@ -128,32 +192,95 @@ impl Pointer {
     /// 3. internal data must be identical without type coercion
     /// 4. internal pointers must be identical
     pub fn eq(&self, rhs: &Self, precision: u8) -> Self {
+        self.eq_with(rhs, precision, Config::default().fuzzy_tolerance.0)
+    }
+
+    /// Like `eq`, with a custom tolerance for numbers at precision 1
+    pub fn eq_with(&self, rhs: &Self, precision: u8, tolerance: f64) -> Self {
         if precision >= 4 {
-            Self::from(match (self, rhs) {
-                (
-                    Self::ConstConst(_) | Self::VarConst(_),
-                    Self::ConstConst(_) | Self::VarConst(_),
-                ) => unsafe {
-                    core::mem::transmute::<Rc<_>, u64>(self.make_const())
-                        == core::mem::transmute::<Rc<_>, u64>(rhs.make_const())
-                },
-                (Self::ConstVar(_) | Self::VarVar(_), Self::ConstVar(_) | Self::VarVar(_)) => unsafe {
-                    core::mem::transmute::<Rc<_>, u64>(self.make_var())
-                        == core::mem::transmute::<Rc<_>, u64>(rhs.make_var())
-                },
-                _ => false,
-            })
+            Self::from(self.same_identity(rhs))
         } else {
-            Self::from(self.with_refs(rhs, |val, rhs| val.eq(rhs, precision)))
+            // a value that contains itself is equal to another one if nothing else about them is different
+            self.visit(Visit::Eq, Some(rhs), || {
+                Self::from(self.with_refs(rhs, |val, rhs| val.eq_with(rhs, precision, tolerance)))
+            })
+            .unwrap_or_else(|| Self::from(true))
         }
     }
 
-    /// Apply the dot operator; object indexing. Otherwise, it returns `undefined`
+    /// Show everything about how a value is stored: what kind of pointer it's behind, the address of the value it
+    /// points to, whether it's frozen, and which kind of value it is, all the way down. Pointers with the same address
+    /// are the same value, so `same_identity` would say they match
+    pub fn debug_string(&self) -> String {
+        format!("{:?}", StorageDebug(self))
+    }
+
+    fn write_debug(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (name, address) = match self {
+            Self::ConstConst(val) => ("const const", Rc::as_ptr(val) as usize),
+            Self::VarConst(val) => ("var const", Rc::as_ptr(&val.borrow()) as usize),
+            Self::ConstVar(val) => ("const var", Rc::as_ptr(val) as usize),
+            Self::VarVar(val) => ("var var", Rc::as_ptr(&val.borrow()) as usize),
+        };
+        let frozen = if self.is_frozen() { " frozen" } else { "" };
+        write!(f, "{name} @{address:#x}{frozen} ")?;
+        self.visit(Visit::Display, None, || {
+            self.with_ref(|val| match val {
+                Value::Boolean(bool) => write!(f, "Boolean({bool})"),
+                Value::String(str) => write!(f, "String({str:?})"),
+                Value::Number(num) => write!(f, "Number({num})"),
+                Value::Keyword(kw) => write!(f, "Keyword({kw})"),
+                Value::Builtin(name) => write!(f, "Builtin({name})"),
+                Value::Function(..) => write!(f, "Function({val})"),
+                Value::Class(_) => write!(f, "Class({val})"),
+                Value::Memoized(_) => write!(f, "Memoized({val})"),
+                Value::Array(arr) => {
+                    f.write_str("Array [")?;
+                    for (idx, item) in arr.iter().enumerate() {
+                        if idx > 0 {
+                            f.write_str(", ")?;
+                        }
+                        item.write_debug(f)?;
+                    }
+                    f.write_char(']')
+                }
+                Value::Object(obj) => {
+                    f.write_str("Object {")?;
+                    for (idx, (key, item)) in obj.into_iter().enumerate() {
+                        if idx > 0 {
+                            f.write_char(',')?;
+                        }
+                        write!(f, " {key:?}: ")?;
+                        item.write_debug(f)?;
+                    }
+                    f.write_str(if obj.is_empty() { "}" } else { " }" })
+                }
+            })
+        })
+        .unwrap_or_else(|| write!(f, "<cycle>"))
+    }
+
+    /// Check if two pointers point to the very same value, rather than just equal ones
+    pub fn same_identity(&self, rhs: &Self) -> bool {
+        match (self, rhs) {
+            (Self::ConstConst(_) | Self::VarConst(_), Self::ConstConst(_) | Self::VarConst(_)) => {
+                Rc::ptr_eq(&self.make_const(), &rhs.make_const())
+            }
+            (Self::ConstVar(_) | Self::VarVar(_), Self::ConstVar(_) | Self::VarVar(_)) => {
+                Rc::ptr_eq(&self.make_var(), &rhs.make_var())
+            }
+            _ => false,
+        }
+    }
+
+    /// Apply the dot operator; object indexing. Otherwise, it returns `undefined`. Strings and arrays can only be
+    /// indexed with whole numbers; anything past the end is `undefined`, but a number that's too big to be an index
+    /// at all is an error
     #[allow(clippy::option_if_let_else, clippy::single_match_else)]
-    pub fn dot(&self, rhs: &Value) -> Self {
+    pub fn dot(&self, rhs: &Value) -> SResult<Self> {
         let allow_modify = matches!(self, Self::ConstVar(_) | Self::VarVar(_));
         let lhs = self.clone_inner();
-        match (lhs, rhs) {
+        Ok(match (lhs, rhs) {
             (Value::Object(mut obj), key) => match obj.get(key) {
                 Some(ptr) => ptr.clone(),
                 None => {
@@ -166,16 +293,17 @@ impl Pointer {
                     val
                 }
             },
-            (Value::String(str), Value::Number(num)) => match *num as i32 {
-                i @ ..=-2 => str.chars().nth(str.len() + ((-i + 1) as usize)),
-                i @ -1.. => str.chars().nth((i + 1) as usize),
-            }
-            .map_or_else(
+            // like arrays, strings start at -1
+            (Value::String(str), Value::Number(num)) => str.chars().nth(index(*num)?).map_or_else(
                 || Self::from(Value::empty_object()),
                 |ch| Self::from(Value::String(String::from(ch).into())),
             ),
+            (Value::Array(arr), Value::Number(num)) => arr
+                .get(index(*num)?)
+                .cloned()
+                .unwrap_or_else(|| Self::from(Value::empty_object())),
             _ => Self::from(Value::empty_object()),
-        }
+        })
     }
 
     /// Try to replace the current value with given value. Returns `Err` if `self` is ptr-const. Doesn't clone if it's not necessary.
@@ -227,6 +355,27 @@ impl Pointer {
         }
     }
 
+    /// Freeze this value and everything inside of it, so no pointer to them can write to them anymore
+    pub fn freeze(&self) {
+        if let Some(var) = self.as_var() {
+            // already frozen; this also stops objects that contain themselves from looping forever
+            if var.borrow().frozen {
+                return;
+            }
+            var.borrow_mut().frozen = true;
+        }
+        self.with_ref(|val| match val {
+            Value::Object(obj) => obj.values().for_each(Self::freeze),
+            Value::Array(arr) => arr.iter().for_each(Self::freeze),
+            _ => {}
+        });
+    }
+
+    /// Check if this points to a frozen value
+    pub fn is_frozen(&self) -> bool {
+        self.as_var().is_some_and(|var| var.borrow().frozen)
+    }
+
     /// Run a function on a reference to the internal value. This does not clone the internal value.
     pub fn with_ref<T, F: FnOnce(&Value) -> T>(&self, func: F) -> T {
         match self {
@@ -243,6 +392,34 @@ impl Pointer {
     }
 }
 
+/// Turn an index that starts at -1 into one that starts at 0, or complain that it isn't a whole number that could
+/// point into a string or an array
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn index(num: f64) -> SResult<usize> {
+    if num.fract() != 0.0 {
+        Err(format!("Indices have to be whole numbers; got `{num}`"))
+    } else if (-1.0..isize::MAX as f64).contains(&num) {
+        Ok((num + 1.0) as usize)
+    } else {
+        Err(format!(
+            "`{num}` is out of range for an index, which start at -1"
+        ))
+    }
+}
+
+/// Pointers are equal when the values behind them are, like `Ord` says, whichever kind of pointer they are
+impl PartialEq for Pointer {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Pointer {}
+
 impl PartialEq<Value> for Pointer {
     fn eq(&self, other: &Value) -> bool {
         self.with_ref(|r| r == other)
@@ -251,7 +428,15 @@ impl PartialEq<Value> for Pointer {
 
 impl PartialOrd for Pointer {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.with_refs(other, std::cmp::PartialOrd::partial_cmp)
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Pointer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // a value that contains itself is ordered by everything else about it, like equality
+        self.visit(Visit::Cmp, Some(other), || self.with_refs(other, Ord::cmp))
+            .unwrap_or(Ordering::Equal)
     }
 }
 
@@ -274,7 +459,7 @@ impl AddAssign for Pointer {
                 val.borrow().borrow_mut().assign(output);
             }
             _ => {}
-        };
+        }
     }
 }
 
@@ -297,7 +482,7 @@ impl SubAssign for Pointer {
                 val.borrow().borrow_mut().assign(output);
             }
             _ => {}
-        };
+        }
     }
 }
 
@@ -320,7 +505,7 @@ impl MulAssign for Pointer {
                 val.borrow().borrow_mut().assign(output);
             }
             _ => {}
-        };
+        }
     }
 }
 
@@ -343,7 +528,7 @@ impl DivAssign for Pointer {
                 val.borrow().borrow_mut().assign(output);
             }
             _ => {}
-        };
+        }
     }
 }
 
@@ -364,7 +549,16 @@ impl Rem for Pointer {
 
 impl RemAssign for Pointer {
     fn rem_assign(&mut self, rhs: Self) {
-        let _ = self.assign(&(self.clone() % rhs));
+        let output = self.clone_inner() % rhs.clone_inner();
+        match self {
+            Self::ConstVar(val) => {
+                val.borrow_mut().assign(output);
+            }
+            Self::VarVar(val) => {
+                val.borrow().borrow_mut().assign(output);
+            }
+            _ => {}
+        }
     }
 }
 
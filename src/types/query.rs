@@ -0,0 +1,88 @@
+use std::fmt::Write;
+
+use super::prelude::*;
+
+impl Value {
+    /// Write a flat object as a URL query string like `a=1&b=2`. Keys and values are written how they're displayed,
+    /// with everything but letters, digits, `-`, `.`, `_`, and `~` percent-encoded. Objects and arrays can't be values,
+    /// since query strings don't have a standard way to nest them
+    pub fn to_query_string(&self) -> SResult<Self> {
+        let Self::Object(obj) = self else {
+            return Err(format!(
+                "`to_query_string` requires an object; got `{self}`"
+            ));
+        };
+        let pairs = obj
+            .iter()
+            .map(|(key, value)| match value.clone_inner() {
+                nested @ (Self::Object(_) | Self::Array(_)) => Err(format!(
+                    "`to_query_string` can't write nested values like `{nested}`"
+                )),
+                value => Ok(format!(
+                    "{}={}",
+                    percent_encode(&key.to_string()),
+                    percent_encode(&value.to_string())
+                )),
+            })
+            .collect::<SResult<Vec<_>>>()?;
+        Ok(Self::from(pairs.join("&").as_str()))
+    }
+
+    /// Read a URL query string into an object. A `?` at the start is skipped, `+` is a space, and a key without an `=`
+    /// has an empty value. Like CSV, every value is a string. If a key shows up more than once, the last one wins
+    pub fn parse_query_string(&self) -> SResult<Self> {
+        let Self::String(text) = self else {
+            return Err(format!(
+                "`from_query_string` requires a string; got `{self}`"
+            ));
+        };
+        let text = text.strip_prefix('?').unwrap_or(text);
+        let mut obj = ObjectMap::new();
+        for pair in text.split('&').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            obj.insert(
+                Self::from(percent_decode(key)?.as_str()),
+                Pointer::from(Self::from(percent_decode(value)?.as_str())).convert(VarType::VarVar),
+            );
+        }
+        Ok(Self::Object(obj))
+    }
+}
+
+fn percent_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(char::from(byte));
+        } else {
+            // writing to a `String` can't fail
+            let _ = write!(encoded, "%{byte:02X}");
+        }
+    }
+    encoded
+}
+
+/// Undo percent-encoding, treating `+` as a space. The bytes have to make valid UTF-8
+fn percent_decode(text: &str) -> SResult<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.bytes();
+    while let Some(byte) = rest.next() {
+        bytes.push(match byte {
+            b'+' => b' ',
+            b'%' => {
+                let hex = [rest.next(), rest.next()];
+                let [Some(high), Some(low)] = hex else {
+                    return Err(format!("Unfinished percent-encoding in `{text}`"));
+                };
+                // `from_str_radix` would take a sign, so `%+1` has to be turned away first
+                if !(high.is_ascii_hexdigit() && low.is_ascii_hexdigit()) {
+                    return Err(format!("Invalid percent-encoding in `{text}`"));
+                }
+                u8::from_str_radix(&String::from_utf8_lossy(&[high, low]), 16)
+                    .map_err(|_| format!("Invalid percent-encoding in `{text}`"))?
+            }
+            byte => byte,
+        });
+    }
+    String::from_utf8(bytes).map_err(|_| format!("`{text}` doesn't decode to valid UTF-8"))
+}
@@ -0,0 +1,54 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A small xorshift random number generator. It's not good enough for cryptography, but it's good enough for dice
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng(u64);
+
+impl Rng {
+    /// Make a generator that always gives the same numbers for the same seed
+    pub const fn seeded(seed: u64) -> Self {
+        // mix the seed with splitmix64 so that small seeds don't start out with mostly zeros
+        let mut mixed = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        mixed = (mixed ^ (mixed >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        mixed = (mixed ^ (mixed >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        mixed ^= mixed >> 31;
+        // xorshift gets stuck at zero forever
+        Self(if mixed == 0 { 1 } else { mixed })
+    }
+
+    pub const fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Get a number from 0 (inclusive) to 1 (exclusive)
+    #[allow(clippy::cast_precision_loss)]
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Get a whole number from 0 (inclusive) to `len` (exclusive). `len` can't be 0
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    pub fn next_index(&mut self, len: usize) -> usize {
+        // the roll is below 1, so this is always below `len`
+        (self.next_f64() * len as f64) as usize
+    }
+}
+
+impl Default for Rng {
+    /// Seed the generator with the current time
+    #[allow(clippy::cast_possible_truncation)]
+    fn default() -> Self {
+        Self::seeded(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_nanos() as u64),
+        )
+    }
+}
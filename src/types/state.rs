@@ -13,8 +13,13 @@ use core::f64::consts as f64;
 #[derive(Debug, PartialEq, Eq)]
 pub struct State {
     current: HashMap<Rc<str>, (Pointer, Lifetime)>,
-    parent: Option<RcMut<State>>,
+    parent: Option<RcMut<Self>>,
     pub undefined: Pointer,
+    pub config: RcMut<Config>,
+    /// Shared by every scope, so seeding it anywhere makes the whole program reproducible
+    pub rng: RcMut<Rng>,
+    /// Functions registered by the host program, which are found when a name isn't defined anywhere else
+    pub builtins: RcMut<Builtins>,
 }
 
 macro_rules! kw {
@@ -27,27 +32,112 @@ macro_rules! kw {
 }
 
 impl State {
+    #[allow(clippy::too_many_lines)]
     pub fn new() -> Self {
         let mut current = HashMap::new();
 
         kw!(current "🥧" => f64::PI);
+        kw!(current "apply_defaults" => Keyword::ApplyDefaults);
+        kw!(current "arity" => Keyword::Arity);
+        kw!(current "assert" => Keyword::Assert);
+        kw!(current "average" => Keyword::Average);
+        kw!(current "bool_to_number" => Keyword::BoolToNumber);
+        kw!(current "break" => Keyword::Break);
+        kw!(current "byte_len" => Keyword::ByteLen);
+        kw!(current "capitalize" => Keyword::Capitalize);
+        kw!(current "char_at" => Keyword::CharAt);
+        kw!(current "chr" => Keyword::Chr);
+        kw!(current "chunk" => Keyword::Chunk);
+        kw!(current "clamp" => Keyword::Clamp);
+        kw!(current "clamp_width" => Keyword::ClampWidth);
         kw!(current "class" => Keyword::Class);
         kw!(current "className" => Keyword::Class);
+        kw!(current "coerce" => Keyword::Coerce);
+        kw!(current "collapse" => Keyword::Collapse);
         kw!(current "const" => Keyword::Const);
+        kw!(current "continue" => Keyword::Continue);
+        kw!(current "cos" => Keyword::Cos);
+        kw!(current "count" => Keyword::Count);
         kw!(current "current" => Keyword::Current);
+        kw!(current "debug" => Keyword::Debug);
+        kw!(current "deep_get" => Keyword::DeepGet);
         kw!(current "delete" => Keyword::Delete);
+        kw!(current "diff" => Keyword::Diff);
+        kw!(current "do_while" => Keyword::DoWhile);
+        kw!(current "drop" => Keyword::Drop);
+        kw!(current "enumerate" => Keyword::Enumerate);
+        kw!(current "equals" => Keyword::Equals);
         kw!(current "eval" => Keyword::Eval);
+        kw!(current "exp" => Keyword::Exp);
         kw!(current "false" => false);
+        kw!(current "flat_map" => Keyword::FlatMap);
         kw!(current "forget" => Keyword::Forget);
+        kw!(current "format" => Keyword::Format);
+        kw!(current "freeze" => Keyword::Freeze);
+        kw!(current "from_base" => Keyword::FromBase);
+        kw!(current "from_csv" => Keyword::FromCsv);
+        kw!(current "from_query_string" => Keyword::FromQueryString);
+        kw!(current "group_by" => Keyword::GroupBy);
+        kw!(current "hashcode" => Keyword::Hashcode);
         kw!(current "if" => Keyword::If);
+        kw!(current "implies" => Keyword::Implies);
+        kw!(current "inf" => Value::Number(f64::INFINITY));
         kw!(current "infinity" => Value::Number(f64::INFINITY));
+        kw!(current "insert" => Keyword::Insert);
+        kw!(current "interpolate" => Keyword::Interpolate);
+        kw!(current "invert" => Keyword::Invert);
+        kw!(current "is_finite" => Keyword::IsFinite);
+        kw!(current "is_nan" => Keyword::IsNan);
+        kw!(current "keys" => Keyword::Keys);
+        kw!(current "len" => Keyword::Len);
+        kw!(current "lines" => Keyword::Lines);
+        kw!(current "ln" => Keyword::Ln);
+        kw!(current "log" => Keyword::Log);
+        kw!(current "matches" => Keyword::Matches);
         kw!(current "maybe" => Boolean::Maybe);
+        kw!(current "memoize" => Keyword::Memoize);
+        kw!(current "nan" => Value::Number(f64::NAN));
         kw!(current "next" => Keyword::Next);
         kw!(current "new" => Keyword::New);
+        kw!(current "omit" => Keyword::Omit);
+        kw!(current "ord" => Keyword::Ord);
+        kw!(current "params" => Keyword::Params);
+        kw!(current "partial" => Keyword::Partial);
+        kw!(current "pick" => Keyword::Pick);
         kw!(current "previous" => Keyword::Previous);
+        kw!(current "product" => Keyword::Product);
+        kw!(current "random" => Keyword::Random);
+        kw!(current "random_int" => Keyword::RandomInt);
+        kw!(current "range" => Keyword::Range);
+        kw!(current "remove_at" => Keyword::RemoveAt);
+        kw!(current "replace" => Keyword::Replace);
+        kw!(current "same_identity" => Keyword::SameIdentity);
+        kw!(current "sample" => Keyword::Sample);
+        kw!(current "seed" => Keyword::Seed);
+        kw!(current "shuffle" => Keyword::Shuffle);
+        kw!(current "sign" => Keyword::Sign);
+        kw!(current "sin" => Keyword::Sin);
+        kw!(current "sort_by_key" => Keyword::SortByKey);
+        kw!(current "sqrt" => Keyword::Sqrt);
+        kw!(current "sum" => Keyword::Sum);
+        kw!(current "swap_case" => Keyword::SwapCase);
+        kw!(current "take" => Keyword::Take);
+        kw!(current "tan" => Keyword::Tan);
+        kw!(current "title_case" => Keyword::TitleCase);
+        kw!(current "to_base" => Keyword::ToBase);
+        kw!(current "to_csv" => Keyword::ToCsv);
+        kw!(current "to_object" => Keyword::ToObject);
+        kw!(current "to_query_string" => Keyword::ToQueryString);
         kw!(current "true" => true);
+        kw!(current "truthy_count" => Keyword::TruthyCount);
         kw!(current "var" => Keyword::Var);
+        kw!(current "vote" => Keyword::Vote);
         kw!(current "when" => Keyword::When);
+        kw!(current "while" => Keyword::While);
+        kw!(current "windows" => Keyword::Windows);
+        kw!(current "with_key" => Keyword::WithKey);
+        kw!(current "without_key" => Keyword::WithoutKey);
+        kw!(current "zip" => Keyword::Zip);
         kw!(current "∞" => Value::Number(f64::INFINITY));
 
         let undefined = Pointer::ConstConst(Rc::new(Value::empty_object()));
@@ -56,14 +146,23 @@ impl State {
             current,
             parent: None,
             undefined,
+            config: rc_mut_new(Config::default()),
+            rng: rc_mut_new(Rng::default()),
+            builtins: rc_mut_new(Builtins::default()),
         }
     }
 
     pub fn from_parent(parent: Rc<RefCell<Self>>) -> Self {
         let undefined = parent.borrow().undefined.clone();
+        let config = parent.borrow().config.clone();
+        let rng = parent.borrow().rng.clone();
+        let builtins = parent.borrow().builtins.clone();
         Self {
             current: HashMap::new(),
             undefined,
+            config,
+            rng,
+            builtins,
             parent: Some(parent),
         }
     }
@@ -78,6 +177,10 @@ impl State {
         if let Some(parent) = &self.parent {
             return (**parent).borrow_mut().get(key);
         }
+        // registered builtins aren't cached, so they can still be registered after they're first looked up
+        if self.builtins.borrow().contains(&key) {
+            return Pointer::ConstConst(Rc::new(Value::Builtin(key)));
+        }
         // otherwise, parse it in global context
         if let Ok(val) = key.parse() {
             let new_val = Pointer::ConstConst(Rc::new(Value::Number(val)));
@@ -114,6 +217,7 @@ impl State {
         }
     }
 
+    #[allow(clippy::mutable_key_type)]
     pub fn locals_to_object(&self) -> BTreeMap<Value, Pointer> {
         self.current
             .iter()
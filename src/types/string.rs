@@ -0,0 +1,469 @@
+use std::{collections::BTreeMap, rc::Rc};
+
+use super::prelude::*;
+
+impl Value {
+    /// Check if the whole string matches a glob `pattern`. `*` matches any run of characters, `?` matches any single
+    /// character, and `\` makes the next character literal. Other values are matched by how they're displayed
+    pub fn matches(&self, pattern: &Self) -> Self {
+        let text = self.to_string().chars().collect::<Vec<_>>();
+        let pattern = pattern.to_string().chars().collect::<Vec<_>>();
+        Self::from(glob(&text, &pattern))
+    }
+
+    /// Replace every `needle` in the string, left to right without overlapping. `replacement` gets each match and
+    /// returns what goes in its place. Other values are searched by how they're displayed, and an empty needle doesn't
+    /// match anything
+    pub fn replace_all(
+        &self,
+        needle: &Self,
+        mut replacement: impl FnMut(&str) -> SResult<Self>,
+    ) -> SResult<Self> {
+        let text = self.to_string();
+        let needle = needle.to_string();
+        if needle.is_empty() {
+            return Ok(Self::from(text.as_str()));
+        }
+        let mut buf = String::new();
+        let mut rest = text.as_str();
+        while let Some(idx) = rest.find(&needle) {
+            buf.push_str(&rest[..idx]);
+            buf.push_str(&replacement(&needle)?.to_string());
+            rest = &rest[idx + needle.len()..];
+        }
+        buf.push_str(rest);
+        Ok(Self::from(buf.as_str()))
+    }
+
+    /// Reverse a string without pulling apart the characters people see as one, like a letter and its accents or a
+    /// flag. Other values are reversed by how they're displayed
+    pub fn reverse_string_preserving_graphemes(&self) -> Self {
+        let text = self.to_string();
+        Self::from(
+            graphemes(&text)
+                .into_iter()
+                .rev()
+                .collect::<String>()
+                .as_str(),
+        )
+    }
+}
+
+/// Split text into grapheme clusters. This isn't all of Unicode's rules, but it keeps combining marks, variation
+/// selectors, skin tones, and tags with the character before them, keeps emoji joined by a zero-width joiner together,
+/// pairs up regional indicators into flags, and keeps `\r\n` together
+fn graphemes(text: &str) -> Vec<&str> {
+    let mut clusters: Vec<&str> = Vec::new();
+    let mut start = 0;
+    let mut prev: Option<char> = None;
+    let mut regional_run = 0;
+    for (idx, ch) in text.char_indices() {
+        let joins = prev.is_some_and(|prev| {
+            is_extender(ch)
+                || prev == '\u{200D}'
+                || (prev == '\r' && ch == '\n')
+                || (is_regional_indicator(ch) && regional_run % 2 == 1)
+        });
+        if !joins && idx > start {
+            clusters.push(&text[start..idx]);
+            start = idx;
+        }
+        regional_run = if is_regional_indicator(ch) {
+            regional_run + 1
+        } else {
+            0
+        };
+        prev = Some(ch);
+    }
+    if start < text.len() {
+        clusters.push(&text[start..]);
+    }
+    clusters
+}
+
+/// Characters that belong to the one before them
+const fn is_extender(ch: char) -> bool {
+    matches!(ch,
+        // combining marks
+        '\u{0300}'..='\u{036F}'
+        | '\u{1AB0}'..='\u{1AFF}'
+        | '\u{1DC0}'..='\u{1DFF}'
+        | '\u{20D0}'..='\u{20FF}'
+        | '\u{FE20}'..='\u{FE2F}'
+        // variation selectors
+        | '\u{FE00}'..='\u{FE0F}'
+        | '\u{E0100}'..='\u{E01EF}'
+        // zero-width joiner, skin tones, and tags
+        | '\u{200D}'
+        | '\u{1F3FB}'..='\u{1F3FF}'
+        | '\u{E0020}'..='\u{E007F}'
+    )
+}
+
+const fn is_regional_indicator(ch: char) -> bool {
+    matches!(ch, '\u{1F1E6}'..='\u{1F1FF}')
+}
+
+/// How many terminal columns a character takes up. This isn't all of Unicode's rules, but it covers the common wide
+/// characters: Hangul, CJK ideographs and punctuation, kana, fullwidth forms, and emoji
+const fn display_width(ch: char) -> usize {
+    match ch as u32 {
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1F64F
+        | 0x1F900..=0x1F9FF
+        | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+fn capitalize_word(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map_or_else(String::new, |first| {
+        first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect()
+    })
+}
+
+/// Match `text` against `pattern`, remembering the last `*` so we can backtrack to it instead of recursing
+fn glob(text: &[char], pattern: &[char]) -> bool {
+    let (mut txt_idx, mut pat_idx) = (0, 0);
+    let mut last_star = None;
+    while txt_idx < text.len() {
+        match pattern.get(pat_idx) {
+            Some('*') => {
+                last_star = Some((pat_idx, txt_idx));
+                pat_idx += 1;
+                continue;
+            }
+            Some('?') => {
+                txt_idx += 1;
+                pat_idx += 1;
+                continue;
+            }
+            Some('\\') if pattern.get(pat_idx + 1) == Some(&text[txt_idx]) => {
+                txt_idx += 1;
+                pat_idx += 2;
+                continue;
+            }
+            Some(ch) if *ch != '\\' && *ch == text[txt_idx] => {
+                txt_idx += 1;
+                pat_idx += 1;
+                continue;
+            }
+            _ => {}
+        }
+        // this character didn't match, so let the last `*` eat it and try again
+        let Some((star_idx, star_txt_idx)) = last_star else {
+            return false;
+        };
+        last_star = Some((star_idx, star_txt_idx + 1));
+        pat_idx = star_idx + 1;
+        txt_idx = star_txt_idx + 1;
+    }
+    pattern[pat_idx..].iter().all(|ch| *ch == '*')
+}
+
+impl Value {
+    /// Fill in a template like `"{0} + {1} = {sum}"`. Numbered placeholders come from the `positional` array, starting at 0,
+    /// and named ones come from the `named` object. Missing ones are left alone, unless `strict` is set.
+    /// Use `{{` and `}}` for literal braces
+    #[allow(clippy::mutable_key_type)]
+    pub fn format(
+        &self,
+        positional: &[Pointer],
+        named: &BTreeMap<Self, Pointer>,
+        strict: bool,
+    ) -> SResult<Self> {
+        let Self::String(template) = self else {
+            return Err(format!("`format` requires a string template; got `{self}`"));
+        };
+        let mut output = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '{' if chars.next_if_eq(&'{').is_some() => output.push('{'),
+                '}' if chars.next_if_eq(&'}').is_some() => output.push('}'),
+                '{' => {
+                    let mut placeholder = String::new();
+                    let mut closed = false;
+                    for ch in chars.by_ref() {
+                        if ch == '}' {
+                            closed = true;
+                            break;
+                        }
+                        placeholder.push(ch);
+                    }
+                    let arg = placeholder.parse::<usize>().map_or_else(
+                        |_| named.get(&Self::from(placeholder.as_str())),
+                        |idx| positional.get(idx),
+                    );
+                    match arg {
+                        _ if strict && !closed => {
+                            return Err(String::from("Unclosed `{` in `format` template"))
+                        }
+                        Some(arg) if closed => output.push_str(&arg.to_string()),
+                        _ if strict => {
+                            return Err(format!(
+                                "Missing argument for `{{{placeholder}}}` in `format`"
+                            ))
+                        }
+                        _ => {
+                            output.push('{');
+                            output.push_str(&placeholder);
+                            if closed {
+                                output.push('}');
+                            }
+                        }
+                    }
+                }
+                ch => output.push(ch),
+            }
+        }
+        Ok(Self::String(output.into()))
+    }
+}
+
+impl Value {
+    /// Fill in a template the same way string literals are interpolated, with variables coming from the `env` object.
+    /// Like undeclared variables, missing ones are just their own names. String literals fill themselves in right away,
+    /// so templates written as literals can escape their currency symbols and `{`s, like `"\\${name}"`
+    pub fn interpolate(&self, env: &Self) -> SResult<Self> {
+        let Self::Object(env) = env else {
+            return Err(format!(
+                "`interpolate` requires an object of variables; got `{env}`"
+            ));
+        };
+        Ok(Self::render(
+            &Self::compile_template(&self.to_string())?,
+            env,
+        ))
+    }
+
+    /// Lex a template once so it can be rendered over and over without lexing it again. `interpolate` is the same as
+    /// compiling and then rendering right away
+    pub fn compile_template(template: &str) -> SResult<Vec<StringSegment>> {
+        crate::lexer::lex_template(&unescape_currency(template))
+    }
+
+    /// Fill in a compiled template with variables from `env`
+    #[allow(clippy::mutable_key_type)]
+    pub fn render(template: &[StringSegment], env: &BTreeMap<Self, Pointer>) -> Self {
+        let lookup = |name: &Rc<str>| {
+            env.get(&Self::from(name.clone()))
+                .map_or_else(|| Pointer::from(name.clone()), Pointer::clone)
+        };
+        let mut buf = String::new();
+        for segment in template {
+            match segment {
+                StringSegment::String(str) => buf.push_str(str),
+                StringSegment::Ident(ident) => buf.push_str(&lookup(ident).to_string()),
+                // only numbers can be bad keys, so looking up a name always works
+                StringSegment::Escudo(lhs, rhs) => {
+                    if let Ok(field) = lookup(lhs).dot(&Self::from(rhs.clone())) {
+                        buf.push_str(&field.to_string());
+                    }
+                }
+            }
+        }
+        Self::from(buf.as_str())
+    }
+
+    /// Get the character at `index`, counting Unicode characters from 0. Negative indices count back from the end,
+    /// and anything out of range is `undefined`
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn char_at(&self, index: &Self) -> SResult<Self> {
+        let (Self::String(str), Self::Number(index)) = (self, index) else {
+            return Err(format!(
+                "`char_at` requires a string and a number; got `{self}` and `{index}`"
+            ));
+        };
+        let index = index.floor() as i64;
+        let index = if index < 0 {
+            str.chars()
+                .count()
+                .checked_sub(index.unsigned_abs() as usize)
+        } else {
+            usize::try_from(index).ok()
+        };
+        Ok(index
+            .and_then(|index| str.chars().nth(index))
+            .map_or_else(Self::empty_object, |ch| Self::from(ch.to_string().as_str())))
+    }
+}
+
+impl Value {
+    /// Get the Unicode code point of the first character of a string
+    pub fn ord(&self) -> SResult<Self> {
+        match self {
+            Self::String(str) => str.chars().next().map_or_else(
+                || {
+                    Err(String::from(
+                        "`ord` requires a string with at least one character",
+                    ))
+                },
+                |ch| Ok(Self::Number(f64::from(u32::from(ch)))),
+            ),
+            other => Err(format!("`ord` requires a string; got `{other}`")),
+        }
+    }
+
+    /// Split a string at each `\n` or `\r\n`. A newline at the very end finishes the last line instead of starting an
+    /// empty one, so a file's contents come out the same whether or not it ends with a newline. Other values have no lines
+    pub fn lines(&self) -> Self {
+        match self {
+            Self::String(str) => Self::from(str.lines().map(Self::from).collect::<Vec<_>>()),
+            _ => Self::from(Vec::new()),
+        }
+    }
+
+    /// Turn uppercase letters into lowercase ones and the other way around. Characters without case, like digits or
+    /// Chinese, don't change, and values that aren't strings are left alone
+    pub fn swap_case(&self) -> Self {
+        let Self::String(str) = self else {
+            return self.clone();
+        };
+        let mut buf = String::with_capacity(str.len());
+        for ch in str.chars() {
+            if ch.is_uppercase() {
+                buf.extend(ch.to_lowercase());
+            } else if ch.is_lowercase() {
+                buf.extend(ch.to_uppercase());
+            } else {
+                buf.push(ch);
+            }
+        }
+        Self::from(buf.as_str())
+    }
+
+    /// How many characters are in a string, items are in an array, or entries are in an object. Everything about
+    /// strings counts characters, except for `byte_len`
+    #[allow(clippy::cast_precision_loss)]
+    pub fn len(&self) -> SResult<Self> {
+        Ok(Self::Number(match self {
+            Self::String(str) => str.chars().count(),
+            Self::Array(arr) => arr.len(),
+            Self::Object(obj) => obj.len(),
+            other => {
+                return Err(format!(
+                    "`len` requires a string, array, or object; got `{other}`"
+                ))
+            }
+        } as f64))
+    }
+
+    /// How many bytes a string takes up as UTF-8. This is more than `len` when there are characters outside of ASCII
+    #[allow(clippy::cast_precision_loss)]
+    pub fn byte_len(&self) -> SResult<Self> {
+        match self {
+            Self::String(str) => Ok(Self::Number(str.len() as f64)),
+            other => Err(format!("`byte_len` requires a string; got `{other}`")),
+        }
+    }
+
+    /// Cut a string down to `width` characters, ending it with `…` if anything had to go. The `…` counts toward the
+    /// width, so the result is never wider than asked. With `wide`, characters that take up two columns in a terminal,
+    /// like Chinese, Japanese, Korean, and most emoji, count as 2
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn clamp_string_width(&self, width: &Self, wide: bool) -> SResult<Self> {
+        let (Self::String(str), Self::Number(width)) = (self, width) else {
+            return Err(format!(
+                "`clamp_width` requires a string and a number; got `{self}` and `{width}`"
+            ));
+        };
+        if width.fract() != 0.0 || *width < 0.0 {
+            return Err(format!(
+                "`clamp_width` requires a whole number that isn't negative; got `{width}`"
+            ));
+        }
+        let width = *width as usize;
+        let char_width = |ch| if wide { display_width(ch) } else { 1 };
+        if str.chars().map(char_width).sum::<usize>() <= width {
+            return Ok(self.clone());
+        }
+        let mut buf = String::new();
+        let mut used = 0;
+        for ch in str.chars() {
+            // leave room for the `…`
+            if used + char_width(ch) >= width {
+                break;
+            }
+            used += char_width(ch);
+            buf.push(ch);
+        }
+        if width > 0 {
+            buf.push('…');
+        }
+        Ok(Self::from(buf.as_str()))
+    }
+
+    /// Make the first letter uppercase and everything after it lowercase. Whitespace at the start is kept and skipped
+    /// over, and values that aren't strings are left alone
+    pub fn capitalize(&self) -> Self {
+        let Self::String(str) = self else {
+            return self.clone();
+        };
+        let start = str.len() - str.trim_start().len();
+        Self::from((str[..start].to_string() + &capitalize_word(&str[start..])).as_str())
+    }
+
+    /// Capitalize every word, where words are separated by whitespace. The whitespace itself doesn't change, and values
+    /// that aren't strings are left alone
+    pub fn title_case(&self) -> Self {
+        let Self::String(str) = self else {
+            return self.clone();
+        };
+        let mut buf = String::with_capacity(str.len());
+        let mut word = String::new();
+        for ch in str.chars() {
+            if ch.is_whitespace() {
+                buf.push_str(&capitalize_word(&core::mem::take(&mut word)));
+                buf.push(ch);
+            } else {
+                word.push(ch);
+            }
+        }
+        buf.push_str(&capitalize_word(&word));
+        Self::from(buf.as_str())
+    }
+
+    /// Make a one-character string from a Unicode code point. Surrogates and other numbers that aren't code points are errors
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn chr(&self) -> SResult<Self> {
+        match self {
+            Self::Number(num)
+                if num.fract() == 0.0 && (0.0..=f64::from(u32::MAX)).contains(num) =>
+            {
+                char::from_u32(*num as u32).map_or_else(
+                    || Err(format!("`{num}` isn't a valid code point")),
+                    |ch| Ok(Self::from(ch.to_string().as_str())),
+                )
+            }
+            other => Err(format!("`{other}` isn't a valid code point")),
+        }
+    }
+}
+
+/// Remove the backslashes from in front of currency symbols and `{`s, so `\\${name}` becomes `${name}`
+fn unescape_currency(template: &str) -> String {
+    let mut unescaped = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch == '\\' && chars.peek().is_some_and(|next| "$£¥€円₽{".contains(*next)) {
+            continue;
+        }
+        unescaped.push(ch);
+    }
+    unescaped
+}
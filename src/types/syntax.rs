@@ -1,21 +1,33 @@
 use std::{fmt::Display, hash::Hash, rc::Rc};
 
-use super::{StringSegment, Token};
+use super::{StringSegment, Token, Value};
 
-#[derive(PartialEq, Debug, Clone, Eq, Hash)]
+#[derive(PartialEq, Debug, Clone, Eq, Hash, PartialOrd, Ord)]
 pub enum Syntax {
-    Declare(VarType, Rc<str>, Lifetime, Box<Syntax>),
-    Function(Vec<Rc<str>>, Box<Syntax>),
-    Operation(Box<Syntax>, Operation, Box<Syntax>),
-    UnaryOperation(UnaryOperation, Box<Syntax>),
+    Declare(VarType, Rc<str>, Lifetime, Box<Self>),
+    Function(Vec<Rc<str>>, Box<Self>),
+    Operation(Box<Self>, Operation, Box<Self>),
+    UnaryOperation(UnaryOperation, Box<Self>),
     Ident(Rc<str>),
     String(Vec<StringSegment>),
-    Block(Vec<Syntax>),
-    Statement(bool, Box<Syntax>, u8),
+    Block(Vec<Self>),
+    Statement(bool, Box<Self>, u8),
+    Array(Vec<Self>),
+    /// `{key: value, ...spread}`. Entries without a key are spreads
+    Object(Vec<(Option<Rc<str>>, Self)>),
+    /// `...value` inside of an array literal
+    Spread(Box<Self>),
+    /// `[body for ident in iterable if condition]`
+    Comprehension(Box<Self>, Rc<str>, Box<Self>, Option<Box<Self>>),
+    /// A value that was already evaluated, like the arguments `partial` binds to a function
+    Literal(Box<Value>),
 }
 
 impl Display for Syntax {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            return crate::parser::write_source(f, self);
+        }
         match self {
             Self::Statement(is_debug, content, count) => {
                 write!(
@@ -39,6 +51,38 @@ impl Display for Syntax {
                 write!(f, "\"")
             }
             Self::Ident(ident) => write!(f, "{ident}"),
+            Self::Array(items) => {
+                write!(f, "[")?;
+                for (idx, item) in items.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                write!(f, "]")
+            }
+            Self::Object(entries) => {
+                write!(f, "{{")?;
+                for (idx, (key, value)) in entries.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ", ")?;
+                    }
+                    match key {
+                        Some(key) => write!(f, "{key}: {value}")?,
+                        None => write!(f, "...{value}")?,
+                    }
+                }
+                write!(f, "}}")
+            }
+            Self::Spread(value) => write!(f, "...{value}"),
+            Self::Literal(value) => write!(f, "{value:?}"),
+            Self::Comprehension(body, ident, iterable, condition) => {
+                write!(f, "[{body} for {ident} in {iterable}")?;
+                if let Some(condition) = condition {
+                    write!(f, " if {condition}")?;
+                }
+                write!(f, "]")
+            }
             Self::Declare(var_type, name, lifetime, value) => {
                 write!(f, "{var_type} {name}{lifetime} = {value}")
             }
@@ -54,16 +98,24 @@ impl Display for Syntax {
             Self::UnaryOperation(UnaryOperation::Increment, operand) => {
                 write!(f, "{operand}++")
             }
+            Self::UnaryOperation(UnaryOperation::PreDecrement, operand) => {
+                write!(f, "--{operand}")
+            }
+            Self::UnaryOperation(UnaryOperation::PreIncrement, operand) => {
+                write!(f, "++{operand}")
+            }
             Self::Function(args, body) => {
                 write!(f, "{args:?} -> {body}")
             }
             Self::UnaryOperation(UnaryOperation::Negate, inner) => write!(f, ";{inner}"),
-            // other => write!(f, "{other:?}"),
+            Self::UnaryOperation(UnaryOperation::Not(count), inner) => {
+                write!(f, "{}{inner}", "!".repeat(*count as usize))
+            } // other => write!(f, "{other:?}"),
         }
     }
 }
 
-#[derive(PartialEq, Eq, Debug, Hash, Clone, Copy)]
+#[derive(PartialEq, Eq, Debug, Hash, Clone, Copy, PartialOrd, Ord)]
 pub enum VarType {
     ConstConst,
     ConstVar,
@@ -83,16 +135,20 @@ impl Display for VarType {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Hash, Eq)]
+#[derive(Debug, Clone, PartialEq, Hash, Eq, PartialOrd, Ord)]
 pub enum UnaryOperation {
     Increment,
     Decrement,
+    PreIncrement,
+    PreDecrement,
     Negate,
+    Not(u8),
     Call(Vec<Syntax>),
 }
 
-#[derive(PartialEq, Eq, Debug, Hash, Clone, Copy)]
+#[derive(PartialEq, Eq, Debug, Hash, Clone, Copy, PartialOrd, Ord)]
 pub enum Operation {
+    /// `=` is assignment; `==` and up compare with a precision of one less than the number of `=`s. See `Pointer::eq`
     Equal(u8),
     Add,
     AddEq,
@@ -112,6 +168,9 @@ pub enum Operation {
     Le,
     Gt,
     Ge,
+    Coalesce,
+    /// `x |> f` calls `f` with `x`
+    Pipe,
 }
 
 impl Display for Operation {
@@ -136,6 +195,8 @@ impl Display for Operation {
             Self::Le => write!(f, "<="),
             Self::Gt => write!(f, ">"),
             Self::Ge => write!(f, ">="),
+            Self::Coalesce => write!(f, "??"),
+            Self::Pipe => write!(f, "|>"),
         }
     }
 }
@@ -158,6 +219,7 @@ impl TryFrom<Token> for Operation {
             Token::Dot => Ok(Self::Dot),
             Token::And => Ok(Self::And),
             Token::Or => Ok(Self::Or),
+            Token::Pipe => Ok(Self::Pipe),
             Token::Arrow => Ok(Self::Arrow),
             Token::LCaret => Ok(Self::Lt),
             Token::LCaretEq => Ok(Self::Le),
@@ -168,7 +230,7 @@ impl TryFrom<Token> for Operation {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash, PartialOrd, Ord)]
 pub enum Lifetime {
     #[default]
     Default,
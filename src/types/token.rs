@@ -1,5 +1,7 @@
 use std::{fmt::Display, rc::Rc};
 
+use super::Keyword;
+
 #[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone)]
 pub enum StringSegment {
     String(Rc<str>),
@@ -22,8 +24,11 @@ impl Display for StringSegment {
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Token {
     Ident(Rc<str>),
+    Keyword(Keyword),
     String(Vec<StringSegment>),
-    Space(u8),
+    Space(u32),
+    /// Only kept when `Config::preserve_comments` is set. Includes the `//` or `/* */`
+    Comment(Rc<str>),
     Equal(u8),
     Bang(u8),
     Question(u8),
@@ -54,6 +59,62 @@ pub enum Token {
     Comma,
     Colon,
     Dot,
+    Ellipsis,
     And,
     Or,
+    Pipe,
+}
+
+impl Display for Token {
+    /// Write the token back out as source code. Whitespace comes back as spaces, which lex to the same count
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ident(ident) => write!(f, "{ident}"),
+            Self::Keyword(kw) => write!(f, "{kw}"),
+            Self::String(segments) => {
+                let text = segments.iter().map(ToString::to_string).collect::<String>();
+                let (start, end) = [('"', '"'), ('\'', '\''), ('`', '`'), ('«', '»')]
+                    .into_iter()
+                    .find(|(_, end)| !text.contains(*end))
+                    .unwrap_or(('"', '"'));
+                write!(f, "{start}{text}{end}")
+            }
+            Self::Space(count) => write!(f, "{}", " ".repeat(*count as usize)),
+            Self::Comment(text) => write!(f, "{text}"),
+            Self::Equal(count) => write!(f, "{}", "=".repeat(*count as usize)),
+            Self::Bang(count) => write!(f, "{}", "!".repeat(*count as usize)),
+            Self::Question(count) => write!(f, "{}", "?".repeat(*count as usize)),
+            Self::Plus => write!(f, "+"),
+            Self::PlusPlus => write!(f, "++"),
+            Self::PlusEq => write!(f, "+="),
+            Self::Tack => write!(f, "-"),
+            Self::TackTack => write!(f, "--"),
+            Self::TackEq => write!(f, "-="),
+            Self::Star => write!(f, "*"),
+            Self::StarEq => write!(f, "*="),
+            Self::Slash => write!(f, "/"),
+            Self::SlashEq => write!(f, "/="),
+            Self::Percent => write!(f, "%"),
+            Self::PercentEq => write!(f, "%="),
+            Self::LCaret => write!(f, "<"),
+            Self::LCaretEq => write!(f, "<="),
+            Self::RCaret => write!(f, ">"),
+            Self::RCaretEq => write!(f, ">="),
+            Self::LParen => write!(f, "("),
+            Self::RParen => write!(f, ")"),
+            Self::LSquirrely => write!(f, "{{"),
+            Self::RSquirrely => write!(f, "}}"),
+            Self::LSquare => write!(f, "["),
+            Self::RSquare => write!(f, "]"),
+            Self::Arrow => write!(f, "->"),
+            Self::Semicolon => write!(f, ";"),
+            Self::Comma => write!(f, ","),
+            Self::Colon => write!(f, ":"),
+            Self::Dot => write!(f, "."),
+            Self::Ellipsis => write!(f, "..."),
+            Self::And => write!(f, "&"),
+            Self::Or => write!(f, "|"),
+            Self::Pipe => write!(f, "|>"),
+        }
+    }
 }
@@ -1,13 +1,12 @@
 use std::{
     cmp::Ordering,
-    collections::BTreeMap,
-    fmt::{Debug, Display},
-    hash::Hash,
-    ops::{Add, BitAnd, BitOr, Div, Mul, Neg, Rem, Sub},
+    fmt::{self, Debug, Display},
+    hash::{DefaultHasher, Hash, Hasher},
+    ops::{BitAnd, BitOr, Neg},
     rc::Rc,
 };
 
-use super::{Pointer, Syntax};
+use super::{pointer::Visit, Config, Memo, ObjectMap, Pointer, Rng, SResult, Syntax};
 
 #[derive(PartialEq, Eq, Debug, Hash, Clone, Copy, PartialOrd, Ord)]
 pub enum Boolean {
@@ -26,6 +25,17 @@ impl Display for Boolean {
     }
 }
 
+impl Boolean {
+    /// The number this counts as in arithmetic
+    pub const fn to_number(self) -> f64 {
+        match self {
+            Self::False => 0.0,
+            Self::Maybe => 0.5,
+            Self::True => 1.0,
+        }
+    }
+}
+
 impl From<bool> for Boolean {
     fn from(value: bool) -> Self {
         if value {
@@ -42,46 +52,76 @@ pub enum Value {
     Boolean(Boolean),
     String(Rc<str>),
     Number(f64),
-    Object(BTreeMap<Value, Pointer>),
+    Object(ObjectMap),
+    Array(Vec<Pointer>),
     Function(Vec<Rc<str>>, Syntax),
     Class(Vec<Syntax>),
     Keyword(Keyword),
+    /// A function the host program registered in [`Builtins`](super::Builtins)
+    Builtin(Rc<str>),
+    /// A function wrapped by `memoize`
+    Memoized(Rc<Memo>),
 }
 
 impl Eq for Value {}
 
 impl Default for Value {
     fn default() -> Self {
-        Self::Object(BTreeMap::new())
+        Self::Object(ObjectMap::new())
+    }
+}
+
+impl Value {
+    /// Where this kind of value goes relative to the other kinds, so that any two values can be ordered
+    const fn rank(&self) -> u8 {
+        match self {
+            Self::Boolean(_) => 0,
+            Self::String(_) => 1,
+            Self::Number(_) => 2,
+            Self::Object(_) => 3,
+            Self::Array(_) => 4,
+            Self::Function(..) => 5,
+            Self::Class(_) => 6,
+            Self::Keyword(_) => 7,
+            Self::Builtin(_) => 8,
+            Self::Memoized(_) => 9,
+        }
     }
 }
 
 impl PartialOrd for Value {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        match unsafe {
-            core::mem::transmute::<_, u64>(core::mem::discriminant(self)).cmp(
-                &core::mem::transmute::<_, u64>(core::mem::discriminant(other)),
-            )
-        } {
-            Ordering::Equal => {}
-            other => return Some(other),
-        }
-        match (self, other) {
-            (Self::Number(lhs), Self::Number(rhs)) => lhs.partial_cmp(rhs),
-            (Self::String(lhs), Self::String(rhs)) => lhs.partial_cmp(rhs),
-            (Self::Boolean(lhs), Self::Boolean(rhs)) => lhs.partial_cmp(rhs),
-            (Self::Keyword(lhs), Self::Keyword(rhs)) => lhs.partial_cmp(rhs),
-            _ => todo!(),
-        }
+        Some(self.cmp(other))
     }
 }
 
+/// Values of different kinds are ordered by their kind, and values of the same kind by what's in them. `NaN` comes
+/// after every other number, functions and classes are ordered by their code, and memoized functions by the function
+/// they wrap and then by which wrapper they are, since each one is only equal to itself
 impl Ord for Value {
     fn cmp(&self, other: &Self) -> Ordering {
-        if let Some(ord) = self.partial_cmp(other) {
-            return ord;
-        };
-        todo!()
+        match (self, other) {
+            // `0` and `-0` are equal here, like they are everywhere else, and so are all the `NaN`s
+            (Self::Number(lhs), Self::Number(rhs)) => lhs
+                .partial_cmp(rhs)
+                .unwrap_or_else(|| lhs.is_nan().cmp(&rhs.is_nan())),
+            (Self::String(lhs), Self::String(rhs)) | (Self::Builtin(lhs), Self::Builtin(rhs)) => {
+                lhs.cmp(rhs)
+            }
+            (Self::Boolean(lhs), Self::Boolean(rhs)) => lhs.cmp(rhs),
+            (Self::Keyword(lhs), Self::Keyword(rhs)) => lhs.cmp(rhs),
+            (Self::Array(lhs), Self::Array(rhs)) => lhs.cmp(rhs),
+            (Self::Object(lhs), Self::Object(rhs)) => lhs.cmp(rhs),
+            (Self::Function(lhs_args, lhs_body), Self::Function(rhs_args, rhs_body)) => {
+                lhs_args.cmp(rhs_args).then_with(|| lhs_body.cmp(rhs_body))
+            }
+            (Self::Class(lhs), Self::Class(rhs)) => lhs.cmp(rhs),
+            (Self::Memoized(lhs), Self::Memoized(rhs)) => lhs
+                .func()
+                .cmp(rhs.func())
+                .then_with(|| Rc::as_ptr(lhs).cmp(&Rc::as_ptr(rhs))),
+            (lhs, rhs) => lhs.rank().cmp(&rhs.rank()),
+        }
     }
 }
 
@@ -96,6 +136,9 @@ impl Debug for Value {
 
 impl Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            return self.fmt_pretty(f, 0);
+        }
         match self {
             Self::Boolean(b) => write!(f, "{b}"),
             Self::String(str) => write!(f, "{str}"),
@@ -111,15 +154,71 @@ impl Display for Value {
                     map.finish()
                 }
             }
-            Self::Function(args, body) => {
-                write!(f, "{args:?} -> {body}")
+            Self::Array(arr) => {
+                let mut list = f.debug_list();
+                for item in arr {
+                    list.entry(&fmt::from_fn(|f| item.fmt_inner(f)));
+                }
+                list.finish()
             }
+            Self::Function(args, body) => crate::parser::write_function(f, args, body),
             Self::Class(syn) => {
                 write!(f, "class {{{syn:?}}}")
             }
 
             Self::Keyword(kw) => write!(f, "{kw}"),
+            Self::Builtin(name) => write!(f, "{name}"),
+            Self::Memoized(memo) => write!(f, "{memo}"),
+        }
+    }
+}
+
+impl Value {
+    /// Show how a value is stored inside the interpreter, like the syntax tree of a function's body. This is for
+    /// debugging the interpreter, not for showing values to people
+    pub fn to_pretty_debug(&self) -> String {
+        match self {
+            Self::Function(args, body) => format!("Function({args:?}, {body:#?})"),
+            Self::Class(syn) => format!("Class({syn:#?})"),
+            Self::Memoized(memo) => format!("Memoized({})", memo.func().to_pretty_debug()),
+            other => format!("{other:?}"),
+        }
+    }
+
+    /// Write objects and arrays with one item per line, indented by how deeply they're nested. This is what `{:#}` does
+    fn fmt_pretty(&self, f: &mut std::fmt::Formatter<'_>, depth: usize) -> std::fmt::Result {
+        const INDENT: &str = "    ";
+        let (open, close) = match self {
+            Self::Object(obj) if !obj.is_empty() => ("object {", "}"),
+            Self::Array(arr) if !arr.is_empty() => ("[", "]"),
+            Self::String(str) if depth > 0 => return write!(f, "{str:?}"),
+            // everything else looks the same either way
+            other => return write!(f, "{other}"),
+        };
+        writeln!(f, "{open}")?;
+        let write_item = |f: &mut std::fmt::Formatter<'_>, item: &Pointer| {
+            item.visit(Visit::Display, None, || {
+                item.with_ref(|item| item.fmt_pretty(f, depth + 1))
+            })
+            .unwrap_or_else(|| write!(f, "<cycle>"))?;
+            writeln!(f, ",")
+        };
+        match self {
+            Self::Object(obj) => {
+                for (key, value) in obj {
+                    write!(f, "{}{key}: ", INDENT.repeat(depth + 1))?;
+                    write_item(f, value)?;
+                }
+            }
+            Self::Array(arr) => {
+                for item in arr {
+                    write!(f, "{}", INDENT.repeat(depth + 1))?;
+                    write_item(f, item)?;
+                }
+            }
+            _ => unreachable!(),
         }
+        write!(f, "{}{close}", INDENT.repeat(depth))
     }
 }
 
@@ -129,6 +228,8 @@ impl Hash for Value {
         match self {
             Self::Boolean(bool) => bool.hash(state),
             Self::String(str) => str.hash(state),
+            // `0` and `-0` are equal, so they have to hash the same too
+            Self::Number(float) if *float == 0.0 => 0f64.to_bits().hash(state),
             Self::Number(float) => (*float).to_bits().hash(state),
             Self::Object(obj) => {
                 let mut vec: Vec<_> = obj.iter().collect::<Vec<_>>();
@@ -138,8 +239,11 @@ impl Hash for Value {
                 }
             }
             Self::Function(inputs, content) => (inputs, content).hash(state),
+            Self::Array(arr) => arr.hash(state),
             Self::Class(body) => body.hash(state),
             Self::Keyword(keyword) => keyword.hash(state),
+            Self::Builtin(name) => name.hash(state),
+            Self::Memoized(memo) => memo.hash(state),
         }
     }
 }
@@ -150,103 +254,98 @@ impl From<bool> for Value {
     }
 }
 
-impl Add for Value {
-    type Output = Self;
-    fn add(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (Self::Number(lhs), Self::Number(rhs)) => Self::Number(lhs + rhs),
-            (Self::Boolean(bool), Self::Number(num)) | (Self::Number(num), Self::Boolean(bool)) => {
-                Self::Number(
-                    match bool {
-                        Boolean::False => 0.0,
-                        Boolean::Maybe => 0.5,
-                        Boolean::True => 1.0,
-                    } + num,
-                )
-            }
-            (Self::String(lhs), rhs) => {
-                Self::String((String::from(&*lhs) + &rhs.to_string()).into())
-            }
-            _ => Self::default(),
+/// The longest a string can get by being multiplied, whatever `max_string_length` says
+const MAX_REPEAT_LEN: usize = 1 << 30;
+
+impl Value {
+    /// Make sure multiplying `str` by `times` gives a string no longer than `max`, or than `MAX_REPEAT_LEN` without
+    /// one. Multiplying by something that isn't finite is always an error
+    #[allow(clippy::cast_precision_loss)]
+    pub fn check_repeat(str: &str, times: f64, max: Option<usize>) -> SResult<()> {
+        if !times.is_finite() {
+            return Err(format!("Can't multiply a string by `{times}`"));
+        }
+        let max = max.map_or(MAX_REPEAT_LEN, |max| max.min(MAX_REPEAT_LEN));
+        let length = (str.chars().count() as f64 * times.abs()).ceil();
+        if length > max as f64 {
+            return Err(format!(
+                "Multiplying this string would make it {length} characters long, which is over the limit of {max}"
+            ));
         }
+        Ok(())
     }
 }
 
-impl Sub for Value {
-    type Output = Self;
-    fn sub(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (Self::Number(lhs), Self::Number(rhs)) => Self::Number(lhs - rhs),
-            _ => Self::default(),
-        }
+impl Value {
+    /// Make a function that calls this one with `bound` as its first arguments. It takes the rest of the
+    /// parameters, so `partial((a, b) -> {a + b}, 5)` is like `(b) -> {5 + b}`
+    pub fn partial(&self, bound: &[Self]) -> SResult<Self> {
+        let Self::Function(params, _) = self else {
+            return Err(format!("`partial` requires a function; got `{self}`"));
+        };
+        let rest = params.get(bound.len()..).unwrap_or_default().to_vec();
+        let args = bound
+            .iter()
+            .map(|arg| Syntax::Literal(Box::new(arg.clone())))
+            .chain(rest.iter().cloned().map(Syntax::Ident))
+            .collect();
+        Ok(Self::Function(
+            rest,
+            Syntax::UnaryOperation(
+                super::UnaryOperation::Call(args),
+                Box::new(Syntax::Literal(Box::new(self.clone()))),
+            ),
+        ))
     }
-}
 
-impl Mul for Value {
-    type Output = Self;
-    #[allow(
-        clippy::cast_possible_truncation,
-        clippy::cast_sign_loss,
-        clippy::cast_precision_loss
-    )]
-    fn mul(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (Self::Number(lhs), Self::Number(rhs)) => Self::Number(lhs * rhs),
-            (Self::String(str), Self::Number(num)) => {
-                let mut str_buf = str.repeat(num.abs().floor() as usize);
-                let portion = ((num.abs() - num.abs().floor()) * str.len() as f64) as usize;
-                if portion > 0 {
-                    str_buf.push_str(&str[0..portion]);
-                }
-                if num.is_sign_negative() {
-                    str_buf = str_buf.chars().rev().collect();
-                }
-                Self::String(str_buf.into())
-            }
-            (Self::Function(lhs_args, lhs), Self::Function(rhs_args, rhs))
-                if lhs_args.len() == 1 =>
-            {
-                Self::Function(
-                    rhs_args,
-                    Syntax::UnaryOperation(
-                        super::UnaryOperation::Call(vec![rhs]),
-                        Box::new(Syntax::Function(lhs_args, Box::new(lhs))),
-                    ),
-                )
-            }
-            _ => Self::default(),
-        }
+    /// How many parameters a function takes, without calling it
+    #[allow(clippy::cast_precision_loss)]
+    pub fn arity(&self) -> SResult<Self> {
+        Ok(Self::Number(self.params_of("arity")?.len() as f64))
     }
-}
 
-impl Div for Value {
-    type Output = Self;
-    fn div(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (Self::Number(lhs), Self::Number(rhs)) => {
-                if rhs == 0.0 {
-                    Self::default()
-                } else {
-                    Self::Number(lhs / rhs)
-                }
-            }
-            _ => Self::default(),
+    /// The names of a function's parameters, in order, without calling it
+    pub fn params(&self) -> SResult<Self> {
+        Ok(Self::from(
+            self.params_of("params")?
+                .iter()
+                .map(|param| Self::from(param.clone()))
+                .collect::<Vec<_>>(),
+        ))
+    }
+
+    /// Get the parameters of a function, looking through `memoize`, or complain that `name` needs a function. Built-in
+    /// functions don't have parameters to look at
+    fn params_of(&self, name: &str) -> SResult<&[Rc<str>]> {
+        match self {
+            Self::Memoized(memo) => memo.func().params_of(name),
+            _ => self.as_function().map(|(params, _)| params).ok_or_else(|| {
+                format!("`{name}` requires a function written in DreamBerd; got `{self}`")
+            }),
         }
     }
 }
 
-impl Rem for Value {
-    type Output = Self;
-    fn rem(self, rhs: Self) -> Self::Output {
+impl Value {
+    /// Like `/`, but dividing a number by zero is an error instead of `undefined`
+    pub fn checked_div(&self, rhs: &Self) -> SResult<Self> {
+        self.check_divisor(rhs)?;
+        Ok(self.clone() / rhs.clone())
+    }
+
+    /// Like `%`, but taking the remainder of dividing a number by zero is an error instead of `undefined`
+    pub fn checked_rem(&self, rhs: &Self) -> SResult<Self> {
+        self.check_divisor(rhs)?;
+        Ok(self.clone() % rhs.clone())
+    }
+
+    /// Make sure dividing this by `rhs`, or taking the remainder, isn't dividing a number by zero
+    pub fn check_divisor(&self, rhs: &Self) -> SResult<()> {
         match (self, rhs) {
-            (Self::Number(lhs), Self::Number(rhs)) => {
-                if rhs == 0.0 {
-                    Self::default()
-                } else {
-                    Self::Number(lhs % rhs)
-                }
+            (Self::Number(_), Self::Number(rhs)) if *rhs == 0.0 => {
+                Err(format!("Can't divide `{self}` by zero"))
             }
-            _ => Self::default(),
+            _ => Ok(()),
         }
     }
 }
@@ -259,7 +358,7 @@ impl Neg for Value {
             Self::Boolean(Boolean::True) => Self::Boolean(Boolean::False),
             Self::Boolean(Boolean::Maybe) => Self::Boolean(Boolean::Maybe),
             Self::Number(num) => Self::Number(-num),
-            Self::String(str) => Self::String(str.chars().rev().collect::<String>().into()),
+            str @ Self::String(_) => str.reverse_string_preserving_graphemes(),
             _ => Self::default(),
         }
     }
@@ -288,7 +387,25 @@ impl BitOr for Value {
 }
 
 impl Value {
+    /// Logical implication, which is the same as `!self | rhs`. Anything follows from something falsey, and something
+    /// truthy implies exactly what `rhs` is. A maybeyey `self` only implies something for sure when `rhs` is truthy
+    pub fn implies(&self, rhs: &Self) -> Self {
+        -Self::Boolean(self.bool()) | rhs.clone()
+    }
+
+    /// check equality with a given precision
+    ///
+    /// 0. any two falsey values are equal; otherwise, values must be identical
+    /// 1. (`==`) like 0, but booleans equal anything with the same truthiness, and values are equal if they display the same ignoring case and surrounding whitespace. Numbers only need to be within about 10%
+    /// 2. (`===`) like 0, but values are equal if they display the same
+    /// 3. (`====`) values must be identical, except that strings are still parsed to compare them to numbers
     pub fn eq(&self, rhs: &Self, precision: u8) -> Self {
+        self.eq_with(rhs, precision, Config::default().fuzzy_tolerance.0)
+    }
+
+    /// Like `eq`, but numbers at precision 1 can be as far apart as `tolerance` instead of about 10%. See
+    /// `Config::fuzzy_tolerance`
+    pub fn eq_with(&self, rhs: &Self, precision: u8, tolerance: f64) -> Self {
         if precision <= 2 && self.bool() == Boolean::False && rhs.bool() == Boolean::False {
             return Self::from(true);
         }
@@ -309,23 +426,32 @@ impl Value {
         }
         match (self, rhs) {
             (&Self::Number(lhs), &Self::Number(rhs)) => {
-                Self::from(lhs == rhs || (precision == 1 && (lhs / rhs).ln().abs() < 0.1))
+                Self::from(lhs == rhs || (precision == 1 && (lhs / rhs).ln().abs() < tolerance))
             }
             (Self::String(lhs), Self::String(rhs)) => Self::from(*lhs == *rhs),
             (&Self::Keyword(lhs), Self::Keyword(rhs)) => Self::from(lhs == *rhs),
+            (Self::Builtin(lhs), Self::Builtin(rhs)) => Self::from(lhs == rhs),
             (Self::String(ref str), &Self::Number(num))
             | (&Self::Number(num), Self::String(ref str)) => {
                 let Ok(str_parse) = str.parse::<f64>() else {
-                    return Self::from(false)
+                    return Self::from(false);
                 };
                 Self::from(
-                    num == str_parse || (precision == 1 && (num / str_parse).ln().abs() < 0.1),
+                    num == str_parse
+                        || (precision == 1 && (num / str_parse).ln().abs() < tolerance),
                 )
             }
+            (Self::Array(lhs), Self::Array(rhs)) => Self::from(
+                lhs.len() == rhs.len()
+                    && lhs
+                        .iter()
+                        .zip(rhs)
+                        .all(|(l, r)| l.eq_with(r, precision, tolerance) != Self::from(false)),
+            ),
             (Self::Object(lhs), Self::Object(rhs)) => Self::from(
                 !lhs.iter().any(|(k, v)| {
                     rhs.get(k)
-                        .map_or(true, |r| r.eq(v, precision) == Self::from(false))
+                        .is_none_or(|r| r.eq_with(v, precision, tolerance) == Self::from(false))
                 }) && !rhs.iter().any(|(k, _)| lhs.get(k).is_none()),
             ),
             _ => Self::from(false),
@@ -344,15 +470,8 @@ impl Value {
                     Boolean::Maybe
                 }
             }
-            Self::String(str) => {
-                if str.is_empty() {
-                    Boolean::False
-                } else {
-                    Boolean::True
-                }
-            }
-            Self::Object(obj) => {
-                if obj.is_empty() {
+            Self::String(_) | Self::Object(_) | Self::Array(_) => {
+                if self.is_empty() {
                     Boolean::False
                 } else {
                     Boolean::True
@@ -362,8 +481,133 @@ impl Value {
         }
     }
 
+    /// Get a definite answer about whether this value is truthy, which is an error if it's maybeyey
+    pub fn coerce_bool_strict(&self) -> SResult<bool> {
+        match self.bool() {
+            Boolean::True => Ok(true),
+            Boolean::False => Ok(false),
+            Boolean::Maybe => Err(format!("`{self}` is maybeyey, but this needs to be sure")),
+        }
+    }
+
+    /// Try to turn this value into a number. Booleans are 0, 0.5, or 1 like in arithmetic, and strings are parsed ignoring surrounding whitespace
+    pub fn to_number(&self) -> Option<f64> {
+        match self {
+            Self::Number(num) => Some(*num),
+            Self::Boolean(bool) => Some(bool.to_number()),
+            Self::String(str) => str.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Turn this value into the type named by `type_name`, which can be `"number"`, `"string"`, `"boolean"`, `"object"`,
+    /// or `"array"`. Numbers come from `to_number`, strings are what's displayed, and booleans are what `bool` says.
+    /// Arrays of `[key, value]` pairs become objects like `to_object`, objects become arrays of those pairs, and
+    /// strings become arrays of their characters. Anything else is an error, except that values that are already the
+    /// right type stay the same
+    pub fn coerce(&self, type_name: &Self) -> SResult<Self> {
+        let Self::String(type_name) = type_name else {
+            return Err(format!("`coerce` requires a type name; got `{type_name}`"));
+        };
+        let impossible = || Err(format!("Can't turn `{self}` into a {type_name}"));
+        match (&**type_name, self) {
+            ("number", _) => self.to_number().map_or_else(impossible, |num| Ok(Self::Number(num))),
+            ("string", _) => Ok(Self::from(self.to_string().as_str())),
+            ("boolean", _) => Ok(Self::Boolean(self.bool())),
+            ("object", Self::Object(_)) | ("array", Self::Array(_)) => Ok(self.clone()),
+            ("object", Self::Array(_)) => self.to_object(),
+            ("array", Self::Object(obj)) => Ok(Self::from(
+                obj.into_iter()
+                    .map(|(key, value)| Self::from(vec![key.clone(), value.clone_inner()]))
+                    .collect::<Vec<_>>(),
+            )),
+            ("array", Self::String(str)) => Ok(Self::from(
+                str.chars()
+                    .map(|ch| Self::from(ch.to_string().as_str()))
+                    .collect::<Vec<_>>(),
+            )),
+            ("object" | "array", _) => impossible(),
+            _ => Err(format!(
+                "`coerce` requires one of `number`, `string`, `boolean`, `object`, or `array`; got `{type_name}`"
+            )),
+        }
+    }
+
+    /// Get the number a boolean counts as in arithmetic: 0 for `false`, 0.5 for `maybe`, and 1 for `true`
+    pub fn bool_to_number(&self) -> SResult<Self> {
+        match self {
+            Self::Boolean(bool) => Ok(Self::Number(bool.to_number())),
+            other => Err(format!(
+                "`bool_to_number` requires a boolean; got `{other}`"
+            )),
+        }
+    }
+
+    /// Turn this value into `true` or `false` for sure. Maybeyey values roll the dice: numbers between 0 and 1 are the
+    /// chance of being `true`, and everything else flips a fair coin unless it's given its own `probability`
+    pub fn collapse(&self, probability: Option<f64>, rng: &mut Rng) -> Self {
+        match (self.bool(), self) {
+            (Boolean::Maybe, Self::Number(num)) if probability.is_none() && !num.is_nan() => {
+                Self::from(rng.next_f64() < *num)
+            }
+            (Boolean::Maybe, _) => Self::from(rng.next_f64() < probability.unwrap_or(0.5)),
+            (bool, _) => Self::Boolean(bool),
+        }
+    }
+
+    /// Apply `count` logical nots. An odd number of `!`s flips the value's `bool()`; an even number just coerces it to a boolean. `maybe` stays `maybe` either way
+    pub fn not(&self, count: u8) -> Self {
+        let bool = Self::from(self.bool());
+        if count % 2 == 1 {
+            -bool
+        } else {
+            bool
+        }
+    }
+
+    /// Hash this value with a fixed hasher. This is reproducible within a run, but may change between versions of DreamBerd-rs
+    pub fn hashcode(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Check if this is an empty string, object, or array
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Self::String(str) => str.is_empty(),
+            Self::Array(arr) => arr.is_empty(),
+            Self::Object(obj) => obj.is_empty(),
+            _ => false,
+        }
+    }
+
+    /// Check if this is `Value::default()` (`undefined`). This is what `??` checks for
+    pub fn is_default(&self) -> bool {
+        matches!(self, Self::Object(obj) if obj.is_empty())
+    }
+
     pub const fn empty_object() -> Self {
-        Self::Object(BTreeMap::new())
+        Self::Object(ObjectMap::new())
+    }
+}
+
+/// Accessors for Rust code that knows what kind of value it wants. Each one is `None` for the wrong kind of value,
+/// without any of the usual conversions
+impl Value {
+    pub fn as_array(&self) -> Option<&[Pointer]> {
+        match self {
+            Self::Array(arr) => Some(arr),
+            _ => None,
+        }
+    }
+
+    /// The parameters and body of a function, as long as it isn't built in
+    pub fn as_function(&self) -> Option<(&[Rc<str>], &Syntax)> {
+        match self {
+            Self::Function(params, body) => Some((params, body)),
+            _ => None,
+        }
     }
 }
 
@@ -399,37 +643,223 @@ impl From<Boolean> for Value {
 
 #[derive(PartialEq, Eq, Debug, Hash, Clone, Copy, PartialOrd, Ord)]
 pub enum Keyword {
+    ApplyDefaults,
+    Arity,
+    Assert,
+    Average,
+    BoolToNumber,
+    Break,
+    ByteLen,
+    Capitalize,
+    CharAt,
+    Chr,
+    Chunk,
+    Clamp,
+    ClampWidth,
     Class,
+    Coerce,
+    Collapse,
     Const,
+    Continue,
+    Cos,
+    Count,
     Current,
+    Debug,
+    DeepGet,
     Delete,
+    Diff,
+    DoWhile,
+    Drop,
+    Enumerate,
+    Equals,
     Eval,
+    Exp,
+    FlatMap,
     Forget,
+    Format,
+    Freeze,
+    FromBase,
+    FromCsv,
+    FromQueryString,
     Function,
+    GroupBy,
+    Hashcode,
     If,
+    Implies,
+    Insert,
+    Interpolate,
+    Invert,
+    IsFinite,
+    IsNan,
+    Keys,
+    Len,
+    Lines,
+    Ln,
+    Log,
+    Matches,
+    Memoize,
     Next,
     New,
+    Omit,
+    Ord,
+    Params,
+    Partial,
+    Pick,
     Previous,
+    Product,
+    Random,
+    RandomInt,
+    Range,
+    RemoveAt,
+    Replace,
+    SameIdentity,
+    Sample,
+    Seed,
+    Shuffle,
+    Sign,
+    Sin,
+    SortByKey,
+    Sqrt,
+    Sum,
+    SwapCase,
+    Take,
+    Tan,
+    TitleCase,
+    ToBase,
+    ToCsv,
+    ToObject,
+    ToQueryString,
+    TruthyCount,
     Var,
+    Vote,
     When,
+    While,
+    Windows,
+    WithKey,
+    WithoutKey,
+    Zip,
+}
+
+impl Keyword {
+    /// Get the keyword the lexer should use for this whole word, if it's reserved
+    pub fn reserved(word: &str) -> Option<Self> {
+        Some(match word {
+            "class" => Self::Class,
+            "const" => Self::Const,
+            "current" => Self::Current,
+            "delete" => Self::Delete,
+            "eval" => Self::Eval,
+            "forget" => Self::Forget,
+            "function" => Self::Function,
+            "if" => Self::If,
+            "new" => Self::New,
+            "next" => Self::Next,
+            "previous" => Self::Previous,
+            "var" => Self::Var,
+            "when" => Self::When,
+            _ => return None,
+        })
+    }
 }
 
 impl Display for Keyword {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            Self::ApplyDefaults => write!(f, "apply_defaults"),
+            Self::Arity => write!(f, "arity"),
+            Self::Assert => write!(f, "assert"),
+            Self::Average => write!(f, "average"),
+            Self::BoolToNumber => write!(f, "bool_to_number"),
+            Self::Break => write!(f, "break"),
+            Self::ByteLen => write!(f, "byte_len"),
+            Self::Capitalize => write!(f, "capitalize"),
+            Self::CharAt => write!(f, "char_at"),
+            Self::Chr => write!(f, "chr"),
+            Self::Chunk => write!(f, "chunk"),
+            Self::Clamp => write!(f, "clamp"),
+            Self::ClampWidth => write!(f, "clamp_width"),
             Self::Class => write!(f, "class"),
+            Self::Coerce => write!(f, "coerce"),
+            Self::Collapse => write!(f, "collapse"),
             Self::Const => write!(f, "const"),
+            Self::Continue => write!(f, "continue"),
+            Self::Cos => write!(f, "cos"),
+            Self::Count => write!(f, "count"),
             Self::Current => write!(f, "current"),
+            Self::Debug => write!(f, "debug"),
+            Self::DeepGet => write!(f, "deep_get"),
+            Self::Drop => write!(f, "drop"),
+            Self::Enumerate => write!(f, "enumerate"),
+            Self::Equals => write!(f, "equals"),
             Self::Eval => write!(f, "eval"),
             Self::Delete => write!(f, "delete"),
+            Self::Diff => write!(f, "diff"),
+            Self::DoWhile => write!(f, "do_while"),
+            Self::Exp => write!(f, "exp"),
+            Self::FlatMap => write!(f, "flat_map"),
             Self::Forget => write!(f, "forget"),
+            Self::Format => write!(f, "format"),
+            Self::Freeze => write!(f, "freeze"),
+            Self::FromBase => write!(f, "from_base"),
+            Self::FromCsv => write!(f, "from_csv"),
+            Self::FromQueryString => write!(f, "from_query_string"),
             Self::Function => write!(f, "function"),
+            Self::GroupBy => write!(f, "group_by"),
+            Self::Hashcode => write!(f, "hashcode"),
             Self::If => write!(f, "if"),
+            Self::Implies => write!(f, "implies"),
+            Self::Insert => write!(f, "insert"),
+            Self::Interpolate => write!(f, "interpolate"),
+            Self::Invert => write!(f, "invert"),
+            Self::IsFinite => write!(f, "is_finite"),
+            Self::IsNan => write!(f, "is_nan"),
+            Self::Keys => write!(f, "keys"),
+            Self::Len => write!(f, "len"),
+            Self::Lines => write!(f, "lines"),
+            Self::Ln => write!(f, "ln"),
+            Self::Log => write!(f, "log"),
+            Self::Matches => write!(f, "matches"),
+            Self::Memoize => write!(f, "memoize"),
             Self::Next => write!(f, "next"),
             Self::New => write!(f, "new"),
+            Self::Omit => write!(f, "omit"),
+            Self::Ord => write!(f, "ord"),
+            Self::Params => write!(f, "params"),
+            Self::Partial => write!(f, "partial"),
+            Self::Pick => write!(f, "pick"),
             Self::Previous => write!(f, "previous"),
+            Self::Product => write!(f, "product"),
+            Self::Random => write!(f, "random"),
+            Self::RandomInt => write!(f, "random_int"),
+            Self::Range => write!(f, "range"),
+            Self::RemoveAt => write!(f, "remove_at"),
+            Self::Replace => write!(f, "replace"),
+            Self::SameIdentity => write!(f, "same_identity"),
+            Self::Sample => write!(f, "sample"),
+            Self::Seed => write!(f, "seed"),
+            Self::Shuffle => write!(f, "shuffle"),
+            Self::Sign => write!(f, "sign"),
+            Self::Sin => write!(f, "sin"),
+            Self::SortByKey => write!(f, "sort_by_key"),
+            Self::Sqrt => write!(f, "sqrt"),
+            Self::Sum => write!(f, "sum"),
+            Self::SwapCase => write!(f, "swap_case"),
+            Self::Take => write!(f, "take"),
+            Self::Tan => write!(f, "tan"),
+            Self::TitleCase => write!(f, "title_case"),
+            Self::ToBase => write!(f, "to_base"),
+            Self::ToCsv => write!(f, "to_csv"),
+            Self::ToObject => write!(f, "to_object"),
+            Self::ToQueryString => write!(f, "to_query_string"),
+            Self::TruthyCount => write!(f, "truthy_count"),
             Self::Var => write!(f, "var"),
+            Self::Vote => write!(f, "vote"),
             Self::When => write!(f, "when"),
+            Self::While => write!(f, "while"),
+            Self::Windows => write!(f, "windows"),
+            Self::WithKey => write!(f, "with_key"),
+            Self::WithoutKey => write!(f, "without_key"),
+            Self::Zip => write!(f, "zip"),
         }
     }
 }
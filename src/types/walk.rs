@@ -0,0 +1,103 @@
+use std::rc::Rc;
+
+use super::prelude::*;
+
+/// Deep traversal for tooling that inspects or rewrites values. Parents are visited before their children, along
+/// with the path of keys (or indices, for arrays) that leads to them from the value `walk` started at. A value that
+/// contains itself isn't visited again inside of itself
+impl Value {
+    pub fn walk<F: FnMut(&[Self], &Self)>(&self, mut visitor: F) {
+        self.walk_inner(&mut Vec::new(), &mut Vec::new(), &mut visitor);
+    }
+
+    /// Like `walk`, but the visitor can change each value in place. Changes happen before the children are visited,
+    /// so a value that gets replaced has the replacement's children visited instead. Values that couldn't be written to
+    /// otherwise, because they're `const` or frozen, are skipped along with everything inside of them
+    pub fn walk_mut<F: FnMut(&[Self], &mut Self)>(&mut self, mut visitor: F) {
+        self.walk_mut_inner(&mut Vec::new(), &mut visitor);
+    }
+
+    /// How many levels of objects and arrays this is. Other values are 0 levels deep, and containers count as a level
+    /// even when they're empty
+    pub fn depth(&self) -> usize {
+        let mut depth = 0;
+        self.walk(|path, val| {
+            let levels = path.len() + usize::from(matches!(val, Self::Object(_) | Self::Array(_)));
+            depth = depth.max(levels);
+        });
+        depth
+    }
+
+    /// How many values `walk` visits, including this one. Anything that shows up in more than one place is counted
+    /// every time it shows up
+    pub fn size(&self) -> usize {
+        let mut size = 0;
+        self.walk(|_, _| size += 1);
+        size
+    }
+
+    fn walk_inner<F: FnMut(&[Self], &Self)>(
+        &self,
+        path: &mut Vec<Self>,
+        ancestors: &mut Vec<*const ()>,
+        visitor: &mut F,
+    ) {
+        visitor(path, self);
+        let children: Vec<(Self, &Pointer)> = match self {
+            Self::Object(obj) => obj.iter().map(|(key, ptr)| (key.clone(), ptr)).collect(),
+            Self::Array(arr) => arr.iter().enumerate().map(index_key).collect(),
+            _ => return,
+        };
+        for (key, ptr) in children {
+            let identity = ptr.as_var().map(|var| Rc::as_ptr(&var).cast::<()>());
+            if identity.is_some_and(|identity| ancestors.contains(&identity)) {
+                continue;
+            }
+            ancestors.extend(identity);
+            path.push(key);
+            ptr.with_ref(|child| child.walk_inner(path, ancestors, visitor));
+            path.pop();
+            if identity.is_some() {
+                ancestors.pop();
+            }
+        }
+    }
+
+    fn walk_mut_inner<F: FnMut(&[Self], &mut Self)>(
+        &mut self,
+        path: &mut Vec<Self>,
+        visitor: &mut F,
+    ) {
+        visitor(path, self);
+        let children: Vec<(Self, &Pointer)> = match self {
+            Self::Object(obj) => obj.iter().map(|(key, ptr)| (key.clone(), ptr)).collect(),
+            Self::Array(arr) => arr.iter().enumerate().map(index_key).collect(),
+            _ => return,
+        };
+        for (key, ptr) in children {
+            path.push(key);
+            with_mut(ptr, |child| child.walk_mut_inner(path, visitor));
+            path.pop();
+        }
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn index_key<T>((idx, ptr): (usize, T)) -> (Value, T) {
+    (Value::Number(idx as f64), ptr)
+}
+
+/// Run a function on a mutable reference to the value inside a pointer, if it's one that could be written to anyway.
+/// Values behind a `const` or frozen pointer are skipped, along with everything inside of them. Values that are
+/// already borrowed are skipped too, which is what stops a value that contains itself from being walked forever
+fn with_mut(ptr: &Pointer, func: impl FnOnce(&mut Value)) {
+    let Some(var) = ptr.as_var() else {
+        return;
+    };
+    let Ok(mut val) = var.try_borrow_mut() else {
+        return;
+    };
+    if !val.frozen {
+        func(&mut val.value);
+    }
+}